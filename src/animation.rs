@@ -1,79 +1,68 @@
-//! Animation state and rendering loop.
+//! Page helpers shared by the animation loops.
 
-use crate::particle::Particle;
-use std::cell::RefCell;
-use std::rc::Rc;
+use crate::error::GlitterbombError;
 use wasm_bindgen::prelude::*;
 use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
 
-thread_local! {
-    pub static ANIMATION_STATE: RefCell<Option<AnimationState>> = const { RefCell::new(None) };
-}
-
-pub struct AnimationState {
-    pub canvas: HtmlCanvasElement,
-    pub ctx: CanvasRenderingContext2d,
-    pub particles: Vec<Particle>,
-    pub is_animating: bool,
-}
-
-impl AnimationState {
-    pub fn new(canvas: HtmlCanvasElement, ctx: CanvasRenderingContext2d) -> Self {
-        Self {
-            canvas,
-            ctx,
-            particles: Vec::new(),
-            is_animating: false,
-        }
-    }
-}
-
 pub fn random() -> f64 {
     js_sys::Math::random()
 }
 
-#[allow(
-    clippy::cast_possible_truncation,
-    clippy::cast_sign_loss,
-    clippy::cast_precision_loss
-)]
-pub fn random_int(min: usize, max: usize) -> usize {
-    (random() * (max - min) as f64).floor() as usize + min
-}
-
 pub fn prefers_reduced_motion() -> bool {
     window()
-        .match_media("(prefers-reduced-motion: reduce)")
         .ok()
+        .and_then(|w| w.match_media("(prefers-reduced-motion: reduce)").ok())
         .flatten()
         .is_some_and(|m| m.matches())
 }
 
-fn window() -> web_sys::Window {
-    web_sys::window().expect("no global window")
+/// Log a problem to the browser console without interrupting the page.
+pub fn warn(err: &GlitterbombError) {
+    web_sys::console::warn_1(&JsValue::from_str(&format!("glitterbomb: {err}")));
+}
+
+pub fn window() -> Result<web_sys::Window, GlitterbombError> {
+    web_sys::window().ok_or(GlitterbombError::NoWindow)
 }
 
-fn document() -> web_sys::Document {
-    window().document().expect("no document")
+fn document() -> Result<web_sys::Document, GlitterbombError> {
+    window()?.document().ok_or(GlitterbombError::NoDocument)
 }
 
-pub fn get_context(canvas: &HtmlCanvasElement) -> CanvasRenderingContext2d {
+pub fn get_context(
+    canvas: &HtmlCanvasElement,
+) -> Result<CanvasRenderingContext2d, GlitterbombError> {
     canvas
         .get_context("2d")
         .ok()
         .flatten()
-        .expect("Could not get 2d context")
-        .dyn_into::<CanvasRenderingContext2d>()
-        .expect("Could not cast to CanvasRenderingContext2d")
+        .and_then(|ctx| ctx.dyn_into::<CanvasRenderingContext2d>().ok())
+        .ok_or(GlitterbombError::ContextUnavailable)
+}
+
+pub fn create_canvas(
+    z_index: i32,
+) -> Result<(HtmlCanvasElement, CanvasRenderingContext2d), GlitterbombError> {
+    let canvas = create_overlay(z_index)?;
+    match get_context(&canvas) {
+        Ok(ctx) => Ok((canvas, ctx)),
+        Err(err) => {
+            canvas.remove();
+            Err(err)
+        }
+    }
 }
 
-pub fn create_canvas(z_index: i32) -> (HtmlCanvasElement, CanvasRenderingContext2d) {
-    let document = document();
+/// Attach a fullscreen, click-through canvas to the page, without taking a
+/// rendering context from it yet.
+pub fn create_overlay(z_index: i32) -> Result<HtmlCanvasElement, GlitterbombError> {
+    let document = document()?;
+    let body = document.body().ok_or(GlitterbombError::NoBody)?;
     let canvas = document
         .create_element("canvas")
-        .expect("Could not create canvas")
-        .dyn_into::<HtmlCanvasElement>()
-        .expect("Could not cast to HtmlCanvasElement");
+        .ok()
+        .and_then(|el| el.dyn_into::<HtmlCanvasElement>().ok())
+        .ok_or(GlitterbombError::CanvasUnavailable)?;
 
     let style = canvas.style();
     let _ = style.set_property("position", "fixed");
@@ -84,119 +73,29 @@ pub fn create_canvas(z_index: i32) -> (HtmlCanvasElement, CanvasRenderingContext
     let _ = style.set_property("pointer-events", "none");
     let _ = style.set_property("z-index", &z_index.to_string());
 
-    document.body().expect("no body").append_child(&canvas).ok();
-
-    let ctx = get_context(&canvas);
-    (canvas, ctx)
+    body.append_child(&canvas)
+        .map_err(|_| GlitterbombError::NoBody)?;
+    Ok(canvas)
 }
 
-#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-pub fn resize_canvas(canvas: &HtmlCanvasElement) {
-    let window = window();
-    let width = window
-        .inner_width()
-        .ok()
-        .and_then(|v| v.as_f64())
-        .unwrap_or(800.0) as u32;
-    let height = window
-        .inner_height()
-        .ok()
-        .and_then(|v| v.as_f64())
-        .unwrap_or(600.0) as u32;
-
-    if canvas.width() != width {
-        canvas.set_width(width);
-    }
-    if canvas.height() != height {
-        canvas.set_height(height);
-    }
+/// A detached canvas and its 2D context for drawing offscreen: a
+/// `<canvas>` element, or an `OffscreenCanvas` inside a worker.
+///
+/// The worker's canvas and context are typed as their DOM counterparts;
+/// they answer to the same methods, which `web-sys` calls by name.
+pub fn scratch_canvas() -> Option<(HtmlCanvasElement, CanvasRenderingContext2d)> {
+    let canvas = match document() {
+        Ok(document) => document.create_element("canvas").ok()?.dyn_into().ok()?,
+        Err(_) => web_sys::OffscreenCanvas::new(1, 1)
+            .ok()?
+            .unchecked_into::<HtmlCanvasElement>(),
+    };
+    let ctx = canvas.get_context("2d").ok().flatten()?.unchecked_into();
+    Some((canvas, ctx))
 }
 
-fn request_animation_frame(f: &Closure<dyn FnMut()>) -> i32 {
-    window()
+pub fn request_animation_frame(f: &Closure<dyn FnMut(f64)>) -> Result<i32, GlitterbombError> {
+    window()?
         .request_animation_frame(f.as_ref().unchecked_ref())
-        .expect("should register RAF")
-}
-
-pub fn start_animation() {
-    let f = Rc::new(RefCell::new(None::<Closure<dyn FnMut()>>));
-    let g = f.clone();
-
-    *g.borrow_mut() = Some(Closure::new(move || {
-        let should_continue = ANIMATION_STATE.with(|state| {
-            let Ok(mut state) = state.try_borrow_mut() else {
-                return true;
-            };
-
-            let Some(ref mut s) = *state else {
-                return false;
-            };
-
-            let width = f64::from(s.canvas.width());
-            let height = f64::from(s.canvas.height());
-            s.ctx.clear_rect(0.0, 0.0, width, height);
-
-            s.particles.retain_mut(|p| {
-                let alive = p.update();
-                if alive {
-                    p.render(&s.ctx);
-                }
-                alive
-            });
-
-            !s.particles.is_empty()
-        });
-
-        if should_continue {
-            request_animation_frame(f.borrow().as_ref().expect("closure exists"));
-        } else {
-            ANIMATION_STATE.with(|state| {
-                if let Ok(mut state) = state.try_borrow_mut() {
-                    if let Some(s) = state.take() {
-                        s.canvas.remove();
-                    }
-                }
-            });
-        }
-    }));
-
-    request_animation_frame(g.borrow().as_ref().expect("closure exists"));
-}
-
-pub fn run_standalone_animation(
-    canvas: HtmlCanvasElement,
-    ctx: CanvasRenderingContext2d,
-    particles: Vec<Particle>,
-) {
-    let f = Rc::new(RefCell::new(None::<Closure<dyn FnMut()>>));
-    let g = f.clone();
-
-    let canvas = Rc::new(canvas);
-    let ctx = Rc::new(ctx);
-    let particles = Rc::new(RefCell::new(particles));
-
-    let canvas_clone = canvas.clone();
-    let ctx_clone = ctx.clone();
-    let particles_clone = particles.clone();
-
-    *g.borrow_mut() = Some(Closure::new(move || {
-        let width = f64::from(canvas_clone.width());
-        let height = f64::from(canvas_clone.height());
-        ctx_clone.clear_rect(0.0, 0.0, width, height);
-
-        let mut parts = particles_clone.borrow_mut();
-        parts.retain_mut(|p| {
-            let alive = p.update();
-            if alive {
-                p.render(&ctx_clone);
-            }
-            alive
-        });
-
-        if !parts.is_empty() {
-            request_animation_frame(f.borrow().as_ref().expect("closure exists"));
-        }
-    }));
-
-    request_animation_frame(g.borrow().as_ref().expect("closure exists"));
+        .map_err(|_| GlitterbombError::AnimationFrameUnavailable)
 }
@@ -0,0 +1,296 @@
+//! Validated construction of [`ConfettiOptions`].
+
+use crate::types::{Color, ConfettiOptions, Gradient, Origin, Shape};
+use std::fmt;
+
+/// Why a set of options was rejected.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OptionsError {
+    /// `colors` is empty, so particles would have nothing to draw with.
+    EmptyColors,
+    /// `shapes` is empty, so no particle shape can be picked.
+    EmptyShapes,
+    /// `ticks` is zero, so particles would never be visible.
+    ZeroTicks,
+    /// `decay` is outside `0.0..=1.0`.
+    DecayOutOfRange(f64),
+    /// `origin` has a coordinate outside `0.0..=1.0`.
+    OriginOutOfRange(Origin),
+    /// `scalar` is zero or negative.
+    NonPositiveScalar(f64),
+    /// A numeric field is NaN or infinite.
+    NotFinite(&'static str),
+    /// `gradient` has no stops, or offsets out of order or outside
+    /// `0.0..=1.0`.
+    InvalidGradient,
+    /// A text shape has no text or a non-positive `scalar`.
+    InvalidText,
+}
+
+impl fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyColors => f.write_str("`colors` must not be empty"),
+            Self::EmptyShapes => f.write_str("`shapes` must not be empty"),
+            Self::ZeroTicks => f.write_str("`ticks` must be greater than zero"),
+            Self::DecayOutOfRange(decay) => {
+                write!(f, "`decay` must be between 0.0 and 1.0, got {decay}")
+            }
+            Self::OriginOutOfRange(o) => write!(
+                f,
+                "`origin` must be within 0.0..=1.0, got ({}, {})",
+                o.x, o.y
+            ),
+            Self::NonPositiveScalar(scalar) => {
+                write!(f, "`scalar` must be positive, got {scalar}")
+            }
+            Self::NotFinite(field) => write!(f, "`{field}` must be a finite number"),
+            Self::InvalidGradient => f.write_str(
+                "`gradient` needs at least one stop, with ascending offsets in 0.0..=1.0",
+            ),
+            Self::InvalidText => {
+                f.write_str("text shapes need non-empty text and a positive `scalar`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for OptionsError {}
+
+impl ConfettiOptions {
+    /// Start building options from the defaults.
+    pub fn builder() -> ConfettiOptionsBuilder {
+        ConfettiOptionsBuilder {
+            opts: Self::default(),
+        }
+    }
+
+    /// Check that every field is in range.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first problem found as an [`OptionsError`].
+    pub fn validate(&self) -> Result<(), OptionsError> {
+        let numbers = [
+            ("angle", self.angle),
+            ("spread", self.spread),
+            ("start_velocity", self.start_velocity),
+            ("decay", self.decay),
+            ("gravity", self.gravity),
+            ("drift", self.drift),
+            ("origin.x", self.origin.x),
+            ("origin.y", self.origin.y),
+            ("scalar", self.scalar),
+        ];
+        if let Some((field, _)) = numbers.iter().find(|(_, v)| !v.is_finite()) {
+            return Err(OptionsError::NotFinite(field));
+        }
+        if self.colors.is_empty() {
+            return Err(OptionsError::EmptyColors);
+        }
+        if self.shapes.is_empty() {
+            return Err(OptionsError::EmptyShapes);
+        }
+        if self.ticks == 0 {
+            return Err(OptionsError::ZeroTicks);
+        }
+        if !(0.0..=1.0).contains(&self.decay) {
+            return Err(OptionsError::DecayOutOfRange(self.decay));
+        }
+        let unit = 0.0..=1.0;
+        if !unit.contains(&self.origin.x) || !unit.contains(&self.origin.y) {
+            return Err(OptionsError::OriginOutOfRange(self.origin));
+        }
+        if self.scalar <= 0.0 {
+            return Err(OptionsError::NonPositiveScalar(self.scalar));
+        }
+        if self.gradient.as_ref().is_some_and(|g| !g.is_valid()) {
+            return Err(OptionsError::InvalidGradient);
+        }
+        let bad_text = |s: &Shape| match s {
+            Shape::Text { text, scalar, .. } => {
+                text.is_empty() || !scalar.is_finite() || *scalar <= 0.0
+            }
+            _ => false,
+        };
+        if self.shapes.iter().any(bad_text) {
+            return Err(OptionsError::InvalidText);
+        }
+        Ok(())
+    }
+}
+
+/// Builder for [`ConfettiOptions`] that validates on [`build`](Self::build).
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct ConfettiOptionsBuilder {
+    opts: ConfettiOptions,
+}
+
+impl ConfettiOptionsBuilder {
+    pub fn particle_count(mut self, count: u32) -> Self {
+        self.opts.particle_count = count;
+        self
+    }
+
+    pub fn angle(mut self, degrees: f64) -> Self {
+        self.opts.angle = degrees;
+        self
+    }
+
+    pub fn spread(mut self, degrees: f64) -> Self {
+        self.opts.spread = degrees;
+        self
+    }
+
+    pub fn start_velocity(mut self, velocity: f64) -> Self {
+        self.opts.start_velocity = velocity;
+        self
+    }
+
+    pub fn decay(mut self, decay: f64) -> Self {
+        self.opts.decay = decay;
+        self
+    }
+
+    pub fn gravity(mut self, gravity: f64) -> Self {
+        self.opts.gravity = gravity;
+        self
+    }
+
+    pub fn drift(mut self, drift: f64) -> Self {
+        self.opts.drift = drift;
+        self
+    }
+
+    pub fn ticks(mut self, ticks: u32) -> Self {
+        self.opts.ticks = ticks;
+        self
+    }
+
+    pub fn origin(mut self, x: f64, y: f64) -> Self {
+        self.opts.origin = Origin { x, y };
+        self
+    }
+
+    pub fn shapes(mut self, shapes: impl IntoIterator<Item = Shape>) -> Self {
+        self.opts.shapes = shapes.into_iter().collect();
+        self
+    }
+
+    pub fn colors(mut self, colors: impl IntoIterator<Item = Color>) -> Self {
+        self.opts.colors = colors.into_iter().collect();
+        self
+    }
+
+    pub fn scalar(mut self, scalar: f64) -> Self {
+        self.opts.scalar = scalar;
+        self
+    }
+
+    pub fn z_index(mut self, z_index: i32) -> Self {
+        self.opts.z_index = z_index;
+        self
+    }
+
+    pub fn flat(mut self, flat: bool) -> Self {
+        self.opts.flat = flat;
+        self
+    }
+
+    pub fn disable_for_reduced_motion(mut self, disable: bool) -> Self {
+        self.opts.disable_for_reduced_motion = disable;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.opts.seed = Some(seed);
+        self
+    }
+
+    pub fn gradient(mut self, gradient: Gradient) -> Self {
+        self.opts.gradient = Some(gradient);
+        self
+    }
+
+    /// Validate and return the options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`OptionsError`] describing the first invalid field.
+    pub fn build(self) -> Result<ConfettiOptions, OptionsError> {
+        self.opts.validate()?;
+        Ok(self.opts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_are_valid() {
+        assert!(ConfettiOptions::builder().build().is_ok());
+    }
+
+    #[test]
+    fn setters_apply() {
+        let opts = ConfettiOptions::builder()
+            .particle_count(10)
+            .origin(0.2, 1.0)
+            .colors([Color::RED])
+            .seed(4)
+            .build()
+            .expect("valid");
+        assert_eq!(opts.particle_count, 10);
+        assert_eq!(opts.origin, Origin { x: 0.2, y: 1.0 });
+        assert_eq!(opts.colors, vec![Color::RED]);
+        assert_eq!(opts.seed, Some(4));
+    }
+
+    #[test]
+    fn empty_palettes_are_rejected() {
+        let colors = ConfettiOptions::builder().colors([]).build();
+        assert_eq!(colors.unwrap_err(), OptionsError::EmptyColors);
+        let shapes = ConfettiOptions::builder().shapes([]).build();
+        assert_eq!(shapes.unwrap_err(), OptionsError::EmptyShapes);
+    }
+
+    #[test]
+    fn ranges_are_checked() {
+        let decay = ConfettiOptions::builder().decay(1.5).build();
+        assert_eq!(decay.unwrap_err(), OptionsError::DecayOutOfRange(1.5));
+        let origin = ConfettiOptions::builder().origin(0.5, -0.1).build();
+        assert!(matches!(
+            origin.unwrap_err(),
+            OptionsError::OriginOutOfRange(_)
+        ));
+        let scalar = ConfettiOptions::builder().scalar(0.0).build();
+        assert_eq!(scalar.unwrap_err(), OptionsError::NonPositiveScalar(0.0));
+        let ticks = ConfettiOptions::builder().ticks(0).build();
+        assert_eq!(ticks.unwrap_err(), OptionsError::ZeroTicks);
+        let gradient = ConfettiOptions::builder()
+            .gradient(Gradient::new([]))
+            .build();
+        assert_eq!(gradient.unwrap_err(), OptionsError::InvalidGradient);
+        let text = ConfettiOptions::builder().shapes([Shape::text("")]).build();
+        assert_eq!(text.unwrap_err(), OptionsError::InvalidText);
+        let huge = Shape::Text {
+            text: "🎉".into(),
+            font: "serif".into(),
+            scalar: f64::INFINITY,
+        };
+        let huge = ConfettiOptions::builder().shapes([huge]).build();
+        assert_eq!(huge.unwrap_err(), OptionsError::InvalidText);
+    }
+
+    #[test]
+    fn non_finite_numbers_are_named() {
+        let err = ConfettiOptions::builder().gravity(f64::NAN).build();
+        assert_eq!(err.unwrap_err(), OptionsError::NotFinite("gravity"));
+        assert_eq!(
+            OptionsError::NotFinite("gravity").to_string(),
+            "`gravity` must be a finite number"
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! 2D canvas renderer for simulation particles
+
+use crate::glyphs;
+use crate::images;
+use crate::sim::{Particle, Simulation};
+use crate::types::{ImageShape, PathShape, Shape};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use web_sys::{CanvasRenderingContext2d, Path2d};
+
+thread_local! {
+    /// `Path2D` objects by path source, built on first use.
+    static PATHS: RefCell<HashMap<String, Path2d>> = RefCell::new(HashMap::new());
+}
+
+/// Draw every live particle of `sim`, interpolated to the current frame,
+/// and return the particles as drawn.
+pub fn render_frame(sim: &Simulation, ctx: &CanvasRenderingContext2d) -> Vec<Particle> {
+    let particles = sim.frame();
+    for p in &particles {
+        render(p, ctx);
+    }
+    particles
+}
+
+/// Draw a single particle onto a 2D context.
+pub fn render(p: &Particle, ctx: &CanvasRenderingContext2d) {
+    let color = p.color();
+    ctx.set_fill_style_str(&format!(
+        "rgba({}, {}, {}, {})",
+        color.r,
+        color.g,
+        color.b,
+        p.opacity() * color.alpha()
+    ));
+    match p.shape() {
+        Shape::Path(path) => return render_path(p, path, ctx),
+        Shape::Text { text, font, .. } => return render_text(p, text, font, ctx),
+        Shape::Image(image) => return render_image(p, image, ctx),
+        _ => {}
+    }
+    ctx.begin_path();
+    match p.shape() {
+        Shape::Circle => render_circle(p, ctx),
+        Shape::Ribbon => render_ribbon(p, ctx),
+        Shape::Square => render_square(p, ctx),
+        _ => render_polygon(p, ctx),
+    }
+    ctx.close_path();
+    ctx.fill();
+}
+
+fn render_circle(p: &Particle, ctx: &CanvasRenderingContext2d) {
+    let (x, y) = p.position();
+    let (rx, ry, rotation) = p.ellipse();
+    ctx.save();
+    let _ = ctx.translate(x, y);
+    let _ = ctx.rotate(rotation);
+    let _ = ctx.scale(rx, ry);
+    let _ = ctx.arc(0.0, 0.0, 1.0, 0.0, 2.0 * PI);
+    ctx.restore();
+}
+
+/// Trace the particle's tilted outline with straight edges.
+fn render_polygon(p: &Particle, ctx: &CanvasRenderingContext2d) {
+    let outline = p.outline();
+    let Some(&(x, y)) = outline.first() else {
+        return;
+    };
+    ctx.move_to(x, y);
+    for (x, y) in outline {
+        ctx.line_to(x, y);
+    }
+}
+
+/// Trace both edges of the ribbon with quadratic curves through the
+/// midpoints between sections, so the strip bends smoothly.
+fn render_ribbon(p: &Particle, ctx: &CanvasRenderingContext2d) {
+    let outline = p.outline();
+    let n = outline.len();
+    if n < 3 {
+        return;
+    }
+    let mid = |a: (f64, f64), b: (f64, f64)| (f64::midpoint(a.0, b.0), f64::midpoint(a.1, b.1));
+    let start = mid(outline[n - 1], outline[0]);
+    ctx.move_to(start.0, start.1);
+    for i in 0..n {
+        let (cx, cy) = outline[i];
+        let (x, y) = mid(outline[i], outline[(i + 1) % n]);
+        ctx.quadratic_curve_to(cx, cy, x, y);
+    }
+}
+
+fn render_square(p: &Particle, ctx: &CanvasRenderingContext2d) {
+    let [first, rest @ ..] = p.corners();
+    ctx.move_to(first.0.floor(), first.1.floor());
+    for (x, y) in rest {
+        ctx.line_to(x.floor(), y.floor());
+    }
+}
+
+fn render_path(p: &Particle, path: &PathShape, ctx: &CanvasRenderingContext2d) {
+    let Some(path_2d) = path_2d(path) else {
+        return;
+    };
+    let m = p.unit_transform();
+    let (centre, scale) = path.normalization();
+    ctx.save();
+    let _ = ctx.transform(m[0], m[1], m[2], m[3], m[4], m[5]);
+    let _ = ctx.scale(scale, scale);
+    let _ = ctx.translate(-centre.0, -centre.1);
+    ctx.fill_with_path_2d(&path_2d);
+    ctx.restore();
+}
+
+fn path_2d(path: &PathShape) -> Option<Path2d> {
+    PATHS.with(|paths| {
+        let mut paths = paths.borrow_mut();
+        if let Some(existing) = paths.get(path.source()) {
+            return Some(existing.clone());
+        }
+        let created = Path2d::new_with_path_string(path.source()).ok()?;
+        paths.insert(path.source().to_string(), created.clone());
+        Some(created)
+    })
+}
+
+fn render_text(p: &Particle, text: &str, font: &str, ctx: &CanvasRenderingContext2d) {
+    let color = p.color();
+    let Some(glyph) = glyphs::glyph(text, font) else {
+        return;
+    };
+    let Some(image) = glyph.paint(color) else {
+        return;
+    };
+    let m = p.unit_transform();
+    let side = glyph.width.max(glyph.height);
+    ctx.save();
+    ctx.set_global_alpha(p.opacity() * color.alpha());
+    let _ = ctx.transform(m[0], m[1], m[2], m[3], m[4], m[5]);
+    let _ = ctx.scale(1.0 / side, 1.0 / side);
+    let _ = ctx.draw_image_with_html_canvas_element_and_dw_and_dh(
+        &image,
+        -glyph.width / 2.0,
+        -glyph.height / 2.0,
+        glyph.width,
+        glyph.height,
+    );
+    ctx.restore();
+}
+
+fn render_image(p: &Particle, image: &ImageShape, ctx: &CanvasRenderingContext2d) {
+    let m = p.unit_transform();
+    ctx.save();
+    ctx.set_global_alpha(p.opacity());
+    let _ = ctx.transform(m[0], m[1], m[2], m[3], m[4], m[5]);
+    images::draw(ctx, image);
+    ctx.restore();
+}
@@ -0,0 +1,151 @@
+//! Color gradients sampled over a particle's lifetime.
+
+use super::Color;
+
+/// `color` reached at `offset`, a fraction `0.0..=1.0` of the lifetime.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GradientStop {
+    pub offset: f64,
+    pub color: Color,
+}
+
+/// Colors a particle passes through from spawn to expiry.
+///
+/// `jitter` randomizes where along the gradient each particle starts: a
+/// particle begins at a random offset in `0.0..jitter` and still ends on the
+/// last stop, so a burst shows a spread of colors early on.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Gradient {
+    stops: Vec<GradientStop>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    jitter: f64,
+}
+
+impl Gradient {
+    /// Gradient through `(offset, color)` stops; offsets are clamped to
+    /// `0.0..=1.0` and sorted.
+    #[must_use]
+    pub fn new(stops: impl IntoIterator<Item = (f64, Color)>) -> Self {
+        let mut stops: Vec<GradientStop> = stops
+            .into_iter()
+            .map(|(offset, color)| GradientStop {
+                offset: offset.clamp(0.0, 1.0),
+                color,
+            })
+            .collect();
+        stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+        Self { stops, jitter: 0.0 }
+    }
+
+    /// Gradient through `colors` at evenly spaced offsets.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn even(colors: impl IntoIterator<Item = Color>) -> Self {
+        let colors: Vec<Color> = colors.into_iter().collect();
+        let last = colors.len().saturating_sub(1).max(1) as f64;
+        Self::new(
+            colors
+                .into_iter()
+                .enumerate()
+                .map(|(i, c)| (i as f64 / last, c)),
+        )
+    }
+
+    /// Let each particle start up to `jitter` (`0.0..=1.0`) along the
+    /// gradient.
+    #[must_use]
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    #[must_use]
+    pub fn stops(&self) -> &[GradientStop] {
+        &self.stops
+    }
+
+    #[must_use]
+    pub fn jitter(&self) -> f64 {
+        self.jitter
+    }
+
+    /// Whether stops exist, with ascending offsets inside `0.0..=1.0`.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        let in_range = |s: &GradientStop| (0.0..=1.0).contains(&s.offset);
+        !self.stops.is_empty()
+            && self.stops.iter().all(in_range)
+            && self.stops.windows(2).all(|w| w[0].offset <= w[1].offset)
+            && (0.0..=1.0).contains(&self.jitter)
+    }
+
+    /// Color at `t` (`0.0..=1.0`), blending linearly between stops in RGBA.
+    #[must_use]
+    pub fn sample(&self, t: f64) -> Color {
+        let (Some(first), Some(last)) = (self.stops.first(), self.stops.last()) else {
+            return Color::TRANSPARENT;
+        };
+        if t <= first.offset {
+            return first.color;
+        }
+        self.stops
+            .windows(2)
+            .find(|w| t <= w[1].offset)
+            .map_or(last.color, |w| {
+                let span = w[1].offset - w[0].offset;
+                let k = if span > 0.0 {
+                    (t - w[0].offset) / span
+                } else {
+                    1.0
+                };
+                mix(w[0].color, w[1].color, k)
+            })
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn mix(a: Color, b: Color, k: f64) -> Color {
+    let channel = |x: u8, y: u8| (f64::from(x) + (f64::from(y) - f64::from(x)) * k).round() as u8;
+    Color::rgba(
+        channel(a.r, b.r),
+        channel(a.g, b.g),
+        channel(a.b, b.b),
+        channel(a.a, b.a),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn embers() -> Gradient {
+        Gradient::even([Color::YELLOW, Color::new(255, 128, 0), Color::RED])
+    }
+
+    #[test]
+    fn samples_blend_between_stops() {
+        let g = embers();
+        assert_eq!(g.sample(0.0), Color::YELLOW);
+        assert_eq!(g.sample(0.25), Color::new(255, 192, 0));
+        assert_eq!(g.sample(0.5), Color::new(255, 128, 0));
+        assert_eq!(g.sample(1.0), Color::RED);
+        assert_eq!(g.sample(2.0), Color::RED);
+    }
+
+    #[test]
+    fn new_sorts_and_clamps_offsets() {
+        let g = Gradient::new([(1.5, Color::RED), (0.0, Color::BLUE)]);
+        assert_eq!(g.stops()[0].color, Color::BLUE);
+        assert!((g.stops()[1].offset - 1.0).abs() < f64::EPSILON);
+        assert!(g.is_valid());
+        assert!(!Gradient::new([]).is_valid());
+    }
+
+    #[test]
+    fn alpha_is_interpolated() {
+        let g = Gradient::new([(0.0, Color::WHITE), (1.0, Color::WHITE.with_alpha(0))]);
+        assert_eq!(g.sample(0.5).a, 128);
+    }
+}
@@ -0,0 +1,217 @@
+//! Colors with alpha, built from RGB, HSL, HSV or CSS color strings, and
+//! gradients between them.
+
+mod gradient;
+mod named;
+mod parse;
+
+pub use gradient::{Gradient, GradientStop};
+pub use parse::ParseColorError;
+
+use std::fmt;
+use std::str::FromStr;
+
+/// RGBA color with 8 bits per channel and straight (unpremultiplied) alpha.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const RED: Self = Self::new(255, 0, 0);
+    pub const GREEN: Self = Self::new(0, 255, 0);
+    pub const BLUE: Self = Self::new(0, 0, 255);
+    pub const YELLOW: Self = Self::new(255, 255, 0);
+    pub const CYAN: Self = Self::new(0, 255, 255);
+    pub const MAGENTA: Self = Self::new(255, 0, 255);
+    pub const WHITE: Self = Self::new(255, 255, 255);
+    pub const BLACK: Self = Self::new(0, 0, 0);
+    pub const TRANSPARENT: Self = Self::rgba(0, 0, 0, 0);
+
+    /// Opaque color from RGB channels.
+    #[must_use]
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self::rgba(r, g, b, 255)
+    }
+
+    #[must_use]
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Opaque color from hue in degrees and saturation/lightness in
+    /// `0.0..=1.0`.
+    #[must_use]
+    pub fn from_hsl(hue: f64, saturation: f64, lightness: f64) -> Self {
+        let (s, l) = (saturation.clamp(0.0, 1.0), lightness.clamp(0.0, 1.0));
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        from_chroma(hue, chroma, l - chroma / 2.0)
+    }
+
+    /// Opaque color from hue in degrees and saturation/value in `0.0..=1.0`.
+    #[must_use]
+    pub fn from_hsv(hue: f64, saturation: f64, value: f64) -> Self {
+        let (s, v) = (saturation.clamp(0.0, 1.0), value.clamp(0.0, 1.0));
+        let chroma = v * s;
+        from_chroma(hue, chroma, v - chroma)
+    }
+
+    /// Parse a CSS color: `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`,
+    /// `rgb()`/`rgba()`, `hsl()`/`hsla()` or a named color.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParseColorError`] if the string is not a supported color.
+    pub fn parse(input: &str) -> Result<Self, ParseColorError> {
+        parse::parse(input)
+    }
+
+    /// Lenient hex parsing, with or without the leading `#`.
+    ///
+    /// Invalid input yields opaque black; use [`Color::parse`] to detect it.
+    #[must_use]
+    pub fn from_hex(hex: &str) -> Self {
+        parse::hex(hex.trim().trim_start_matches('#')).unwrap_or(Self::BLACK)
+    }
+
+    #[must_use]
+    pub const fn with_alpha(self, a: u8) -> Self {
+        Self { a, ..self }
+    }
+
+    /// Alpha as a fraction in `0.0..=1.0`.
+    #[must_use]
+    pub fn alpha(self) -> f64 {
+        f64::from(self.a) / 255.0
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn from_chroma(hue: f64, chroma: f64, lift: f64) -> Color {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let second = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (red, green, blue) = match sector {
+        s if s < 1.0 => (chroma, second, 0.0),
+        s if s < 2.0 => (second, chroma, 0.0),
+        s if s < 3.0 => (0.0, chroma, second),
+        s if s < 4.0 => (0.0, second, chroma),
+        s if s < 5.0 => (second, 0.0, chroma),
+        _ => (chroma, 0.0, second),
+    };
+    let byte = |v: f64| ((v + lift) * 255.0).round().clamp(0.0, 255.0) as u8;
+    Color::new(byte(red), byte(green), byte(blue))
+}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Formats as `#rrggbb`, or `#rrggbbaa` when not fully opaque.
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)?;
+        if self.a < 255 {
+            write!(f, "{:02x}", self.a)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_forms() {
+        assert_eq!(Color::parse("#f00"), Ok(Color::RED));
+        assert_eq!(Color::parse("#f008"), Ok(Color::rgba(255, 0, 0, 0x88)));
+        assert_eq!(Color::parse("#12ABef"), Ok(Color::new(0x12, 0xab, 0xef)));
+        assert_eq!(
+            Color::parse("#12abef80"),
+            Ok(Color::rgba(0x12, 0xab, 0xef, 0x80))
+        );
+        assert!(matches!(
+            Color::parse("#ff"),
+            Err(ParseColorError::InvalidHex(_))
+        ));
+        assert!(matches!(
+            Color::parse("#ggg"),
+            Err(ParseColorError::InvalidHex(_))
+        ));
+    }
+
+    #[test]
+    fn from_hex_never_panics() {
+        assert_eq!(Color::from_hex("ff"), Color::BLACK);
+        assert_eq!(Color::from_hex("#zzzzzz"), Color::BLACK);
+        assert_eq!(Color::from_hex("é"), Color::BLACK);
+    }
+
+    #[test]
+    fn rgb_functions() {
+        assert_eq!(Color::parse("rgb(255, 0, 0)"), Ok(Color::RED));
+        assert_eq!(
+            Color::parse("rgba(0, 0, 255, 0.5)"),
+            Ok(Color::rgba(0, 0, 255, 128))
+        );
+        assert_eq!(
+            Color::parse("rgb(100% 0% 0% / 25%)"),
+            Ok(Color::rgba(255, 0, 0, 64))
+        );
+        assert!(matches!(
+            Color::parse("rgb(1, 2)"),
+            Err(ParseColorError::InvalidFunction(_))
+        ));
+    }
+
+    #[test]
+    fn hsl_and_hsv() {
+        assert_eq!(
+            Color::parse("hsl(120, 100%, 50%)"),
+            Ok(Color::new(0, 255, 0))
+        );
+        assert_eq!(
+            Color::parse("hsla(240deg 100% 50% / 0)"),
+            Ok(Color::BLUE.with_alpha(0))
+        );
+        assert_eq!(Color::from_hsl(0.0, 0.0, 1.0), Color::WHITE);
+        assert_eq!(Color::from_hsl(-120.0, 1.0, 0.5), Color::BLUE);
+        assert_eq!(Color::from_hsv(60.0, 1.0, 1.0), Color::YELLOW);
+        assert_eq!(Color::from_hsv(300.0, 1.0, 0.5), Color::new(128, 0, 128));
+    }
+
+    #[test]
+    fn named_colors() {
+        assert_eq!(
+            Color::parse("RebeccaPurple"),
+            Ok(Color::new(0x66, 0x33, 0x99))
+        );
+        assert_eq!(Color::parse(" gold "), Ok(Color::new(255, 215, 0)));
+        assert_eq!(Color::parse("transparent"), Ok(Color::TRANSPARENT));
+        assert!(matches!(
+            Color::parse("blurple"),
+            Err(ParseColorError::Unknown(_))
+        ));
+        // Hex digits only count after a `#`.
+        assert_eq!(
+            Color::parse("bad"),
+            Err(ParseColorError::Unknown("bad".to_string()))
+        );
+        assert_eq!(Color::parse("  "), Err(ParseColorError::Empty));
+    }
+
+    #[test]
+    fn display_round_trips() {
+        assert_eq!(Color::RED.to_string(), "#ff0000");
+        let translucent = Color::rgba(1, 2, 3, 4);
+        assert_eq!(translucent.to_string(), "#01020304");
+        assert_eq!(translucent.to_string().parse::<Color>(), Ok(translucent));
+    }
+}
@@ -0,0 +1,144 @@
+//! Parsing of CSS color strings.
+
+use super::{named, Color};
+use std::fmt;
+
+/// Why a string could not be parsed as a [`Color`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseColorError {
+    /// The input was empty or only whitespace.
+    Empty,
+    /// A `#` color without 3, 4, 6 or 8 hex digits.
+    InvalidHex(String),
+    /// An `rgb()`/`rgba()`/`hsl()`/`hsla()` call with bad arguments, or an
+    /// unsupported color function.
+    InvalidFunction(String),
+    /// Not a hex color, color function or CSS color name.
+    Unknown(String),
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("empty color string"),
+            Self::InvalidHex(s) => write!(f, "invalid hex color `{s}`"),
+            Self::InvalidFunction(s) => write!(f, "invalid color function `{s}`"),
+            Self::Unknown(s) => write!(f, "unknown color `{s}`"),
+        }
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+pub fn parse(input: &str) -> Result<Color, ParseColorError> {
+    let s = input.trim().to_ascii_lowercase();
+    if s.is_empty() {
+        return Err(ParseColorError::Empty);
+    }
+    if let Some(digits) = s.strip_prefix('#') {
+        return hex(digits).ok_or_else(|| ParseColorError::InvalidHex(input.to_string()));
+    }
+    if let Some((name, args)) = s.strip_suffix(')').and_then(|f| f.split_once('(')) {
+        let color = match name.trim() {
+            "rgb" | "rgba" => rgb(args),
+            "hsl" | "hsla" => hsl(args),
+            _ => None,
+        };
+        return color.ok_or_else(|| ParseColorError::InvalidFunction(input.to_string()));
+    }
+    if s == "transparent" {
+        return Ok(Color::TRANSPARENT);
+    }
+    named::lookup(&s)
+        .map(|rgb| {
+            let [_, r, g, b] = rgb.to_be_bytes();
+            Color::new(r, g, b)
+        })
+        .ok_or_else(|| ParseColorError::Unknown(input.to_string()))
+}
+
+/// `rgb`, `rgba`, `rrggbb` or `rrggbbaa` hex digits, without the `#`.
+pub fn hex(digits: &str) -> Option<Color> {
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let nibble = |i: usize| u8::from_str_radix(&digits[i..=i], 16).ok();
+    let byte = |i: usize| u8::from_str_radix(&digits[2 * i..2 * i + 2], 16).ok();
+    match digits.len() {
+        3 | 4 => {
+            let short = |i: usize| nibble(i).map(|n| n * 17);
+            let a = if digits.len() == 4 { short(3)? } else { 255 };
+            Some(Color::rgba(short(0)?, short(1)?, short(2)?, a))
+        }
+        6 | 8 => {
+            let a = if digits.len() == 8 { byte(3)? } else { 255 };
+            Some(Color::rgba(byte(0)?, byte(1)?, byte(2)?, a))
+        }
+        _ => None,
+    }
+}
+
+fn rgb(args: &str) -> Option<Color> {
+    let ([r, g, b], a) = arguments(args)?;
+    Some(Color::rgba(
+        channel(r)?,
+        channel(g)?,
+        channel(b)?,
+        alpha(a)?,
+    ))
+}
+
+fn hsl(args: &str) -> Option<Color> {
+    let ([h, s, l], a) = arguments(args)?;
+    let hue = number(h.strip_suffix("deg").unwrap_or(h))?;
+    let color = Color::from_hsl(hue, fraction(s)?, fraction(l)?);
+    Some(color.with_alpha(alpha(a)?))
+}
+
+/// Split `a, b, c[, alpha]` or `a b c[ / alpha]` into three components and
+/// an optional alpha.
+fn arguments(args: &str) -> Option<([&str; 3], Option<&str>)> {
+    let (args, slash_alpha) = match args.split_once('/') {
+        Some((rest, a)) => (rest, Some(a.trim())),
+        None => (args, None),
+    };
+    let parts: Vec<&str> = if args.contains(',') {
+        args.split(',').map(str::trim).collect()
+    } else {
+        args.split_whitespace().collect()
+    };
+    match (parts.as_slice(), slash_alpha) {
+        ([x, y, z], a) => Some(([x, y, z], a)),
+        ([x, y, z, a], None) => Some(([x, y, z], Some(a))),
+        _ => None,
+    }
+}
+
+fn number(s: &str) -> Option<f64> {
+    s.trim().parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+/// `50%` or a bare `0.0..=1.0` number, clamped to `0.0..=1.0`.
+fn fraction(s: &str) -> Option<f64> {
+    let value = match s.strip_suffix('%') {
+        Some(pct) => number(pct)? / 100.0,
+        None => number(s)?,
+    };
+    Some(value.clamp(0.0, 1.0))
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn channel(s: &str) -> Option<u8> {
+    let value = match s.strip_suffix('%') {
+        Some(pct) => number(pct)? * 2.55,
+        None => number(s)?,
+    };
+    Some(value.clamp(0.0, 255.0).round() as u8)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn alpha(s: Option<&str>) -> Option<u8> {
+    s.map_or(Some(255), |a| {
+        fraction(a).map(|v| (v * 255.0).round() as u8)
+    })
+}
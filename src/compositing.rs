@@ -0,0 +1,195 @@
+//! How confetti shares a canvas with what is already drawn on it.
+
+use crate::sim::Particle;
+use std::fmt;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+/// Pixels added around each particle's box to cover antialiased edges.
+const MARGIN: f64 = 2.0;
+
+/// Above this many dirty rectangles, restore their union in one copy.
+const MAX_RECTS: usize = 64;
+
+/// Callback that repaints the host's scene before confetti is drawn.
+pub type Repaint = Rc<dyn Fn(&CanvasRenderingContext2d)>;
+
+/// What happens to the rest of the canvas while confetti animates.
+#[derive(Clone, Default)]
+pub enum Compositing {
+    /// Clear the whole canvas every frame. Right for canvases that hold
+    /// nothing but confetti.
+    #[default]
+    Clear,
+    /// Snapshot the canvas when the animation starts and, each frame, copy
+    /// back only the rectangles particles covered. For static content such
+    /// as a chart; anything the host draws meanwhile is overwritten where
+    /// confetti passes.
+    Preserve,
+    /// Call the function to repaint the whole scene each frame, then draw
+    /// confetti on top. Nothing is cleared, so an animated game can redraw
+    /// itself. The callback must not fire confetti on the same instance.
+    Repaint(Repaint),
+}
+
+impl fmt::Debug for Compositing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Clear => f.write_str("Clear"),
+            Self::Preserve => f.write_str("Preserve"),
+            Self::Repaint(_) => f.write_str("Repaint(..)"),
+        }
+    }
+}
+
+/// Rectangle as `(x, y, width, height)` in canvas pixels.
+type Rect = (f64, f64, f64, f64);
+
+/// Per-animation state for a [`Compositing`] mode.
+#[derive(Default)]
+pub struct Layer {
+    backdrop: Option<HtmlCanvasElement>,
+    dirty: Vec<Rect>,
+}
+
+impl Layer {
+    /// Make the canvas ready for the next frame of confetti.
+    pub fn erase(
+        &mut self,
+        mode: &Compositing,
+        canvas: &HtmlCanvasElement,
+        ctx: &CanvasRenderingContext2d,
+    ) {
+        match mode {
+            Compositing::Clear => clear(canvas, ctx),
+            Compositing::Repaint(repaint) => repaint(ctx),
+            Compositing::Preserve => {
+                if self.backdrop.is_none() {
+                    self.backdrop = snapshot(canvas);
+                }
+                self.restore_dirty(ctx);
+            }
+        }
+    }
+
+    /// Remember where `particles` were drawn, `scale` canvas pixels per
+    /// unit, to be restored next frame.
+    pub fn track(&mut self, particles: &[Particle], scale: f64) {
+        self.dirty = particles
+            .iter()
+            .map(|p| {
+                let (x0, y0, x1, y1) = p.bounds();
+                let (x0, y0, x1, y1) = (x0 * scale, y0 * scale, x1 * scale, y1 * scale);
+                (
+                    (x0 - MARGIN).floor(),
+                    (y0 - MARGIN).floor(),
+                    (x1 - x0 + 2.0 * MARGIN).ceil() + 1.0,
+                    (y1 - y0 + 2.0 * MARGIN).ceil() + 1.0,
+                )
+            })
+            .collect();
+        if self.dirty.len() > MAX_RECTS {
+            self.dirty = vec![union(&self.dirty)];
+        }
+    }
+
+    /// Remove the confetti, leaving the canvas as the mode found it, and
+    /// forget the snapshot.
+    pub fn restore(
+        &mut self,
+        mode: &Compositing,
+        canvas: &HtmlCanvasElement,
+        ctx: &CanvasRenderingContext2d,
+    ) {
+        match mode {
+            Compositing::Preserve => self.restore_dirty(ctx),
+            _ => self.erase(mode, canvas, ctx),
+        }
+        *self = Self::default();
+    }
+
+    fn restore_dirty(&mut self, ctx: &CanvasRenderingContext2d) {
+        let Some(backdrop) = &self.backdrop else {
+            return;
+        };
+        for &(x, y, w, h) in &self.dirty {
+            ctx.clear_rect(x, y, w, h);
+            let _ = ctx
+                .draw_image_with_html_canvas_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                    backdrop, x, y, w, h, x, y, w, h,
+                );
+        }
+        self.dirty.clear();
+    }
+}
+
+fn clear(canvas: &HtmlCanvasElement, ctx: &CanvasRenderingContext2d) {
+    let width = f64::from(canvas.width());
+    let height = f64::from(canvas.height());
+    ctx.clear_rect(0.0, 0.0, width, height);
+}
+
+/// Copy of the canvas's current pixels in an offscreen canvas.
+fn snapshot(canvas: &HtmlCanvasElement) -> Option<HtmlCanvasElement> {
+    let copy = web_sys::window()?
+        .document()?
+        .create_element("canvas")
+        .ok()?
+        .dyn_into::<HtmlCanvasElement>()
+        .ok()?;
+    copy.set_width(canvas.width());
+    copy.set_height(canvas.height());
+    let ctx = copy
+        .get_context("2d")
+        .ok()
+        .flatten()?
+        .dyn_into::<CanvasRenderingContext2d>()
+        .ok()?;
+    ctx.draw_image_with_html_canvas_element(canvas, 0.0, 0.0)
+        .ok()?;
+    Some(copy)
+}
+
+fn union(rects: &[Rect]) -> Rect {
+    let (x0, y0, x1, y1) = rects.iter().fold(
+        (
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        ),
+        |(x0, y0, x1, y1), &(x, y, w, h)| (x0.min(x), y0.min(y), x1.max(x + w), y1.max(y + h)),
+    );
+    (x0, y0, x1 - x0, y1 - y0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn union_covers_every_rect() {
+        let rects = [(0.0, 5.0, 10.0, 10.0), (20.0, -3.0, 5.0, 4.0)];
+        assert_eq!(union(&rects), (0.0, -3.0, 25.0, 18.0));
+    }
+
+    #[test]
+    fn many_dirty_rects_collapse_into_one() {
+        use crate::sim::Simulation;
+        use crate::types::ConfettiOptions;
+
+        let mut sim = Simulation::new(|| 0.5);
+        let opts = ConfettiOptions {
+            particle_count: 100,
+            seed: Some(2),
+            ..Default::default()
+        };
+        sim.spawn(&opts, 200.0, 200.0);
+        let mut layer = Layer::default();
+        layer.track(&sim.frame()[..10], 2.0);
+        assert_eq!(layer.dirty.len(), 10);
+        layer.track(&sim.frame(), 2.0);
+        assert_eq!(layer.dirty.len(), 1);
+    }
+}
@@ -0,0 +1,237 @@
+//! Serialization of confetti options and loading them from config files.
+//!
+//! Field names match the Rust field names (`particle_count`, `start_velocity`,
+//! ...), shapes are lowercase strings (custom paths are `{ path = "<d>" }`)
+//! and colors are CSS color strings, written back as `#rrggbb` or
+//! `#rrggbbaa`. Any field missing from a document falls back to its
+//! [`ConfettiOptions::default`] value.
+
+use crate::builder::OptionsError;
+use crate::types::Color;
+#[cfg(any(feature = "json", feature = "toml", feature = "ron"))]
+use crate::types::ConfettiOptions;
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+#[cfg(any(feature = "json", feature = "toml", feature = "ron"))]
+use std::path::Path;
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(ColorVisitor)
+    }
+}
+
+struct ColorVisitor;
+
+impl Visitor<'_> for ColorVisitor {
+    type Value = Color;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a CSS color string like \"#ff0000\" or \"rgba(255, 0, 0, 0.5)\"")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Color, E> {
+        Color::parse(value).map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+}
+
+/// Errors from loading confetti options.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file could not be read.
+    Io(std::io::Error),
+    /// The document is not valid for the given format.
+    Parse(String),
+    /// The file extension does not map to an enabled format.
+    UnsupportedFormat(String),
+    /// The document parsed but a field is out of range.
+    Invalid(OptionsError),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "could not read options: {err}"),
+            Self::Parse(msg) => write!(f, "invalid options: {msg}"),
+            Self::UnsupportedFormat(ext) => write!(f, "unsupported options format `{ext}`"),
+            Self::Invalid(err) => write!(f, "invalid options: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Invalid(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Parse options from a JSON document.
+///
+/// # Errors
+///
+/// Returns [`ConfigError::Parse`] if the document is malformed, or
+/// [`ConfigError::Invalid`] if a field is out of range.
+#[cfg(feature = "json")]
+pub fn from_json(source: &str) -> Result<ConfettiOptions, ConfigError> {
+    serde_json::from_str(source)
+        .map_err(|err| ConfigError::Parse(err.to_string()))
+        .and_then(validated)
+}
+
+/// Parse options from a TOML document.
+///
+/// # Errors
+///
+/// Returns [`ConfigError::Parse`] if the document is malformed, or
+/// [`ConfigError::Invalid`] if a field is out of range.
+#[cfg(feature = "toml")]
+pub fn from_toml(source: &str) -> Result<ConfettiOptions, ConfigError> {
+    toml::from_str(source)
+        .map_err(|err| ConfigError::Parse(err.to_string()))
+        .and_then(validated)
+}
+
+/// Parse options from a RON document.
+///
+/// # Errors
+///
+/// Returns [`ConfigError::Parse`] if the document is malformed, or
+/// [`ConfigError::Invalid`] if a field is out of range.
+#[cfg(feature = "ron")]
+pub fn from_ron(source: &str) -> Result<ConfettiOptions, ConfigError> {
+    ron::from_str(source)
+        .map_err(|err| ConfigError::Parse(err.to_string()))
+        .and_then(validated)
+}
+
+#[cfg(any(feature = "json", feature = "toml", feature = "ron"))]
+fn validated(opts: ConfettiOptions) -> Result<ConfettiOptions, ConfigError> {
+    opts.validate().map(|()| opts).map_err(ConfigError::Invalid)
+}
+
+/// Load options from a file, picking the format from its extension
+/// (`.json`, `.toml` or `.ron`, each behind the feature of the same name).
+///
+/// # Errors
+///
+/// Returns [`ConfigError`] if the file cannot be read, its extension has no
+/// enabled format, or the document is malformed or out of range.
+#[cfg(any(feature = "json", feature = "toml", feature = "ron"))]
+pub fn load(path: impl AsRef<Path>) -> Result<ConfettiOptions, ConfigError> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path).map_err(ConfigError::Io)?;
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    match ext {
+        #[cfg(feature = "json")]
+        "json" => from_json(&source),
+        #[cfg(feature = "toml")]
+        "toml" => from_toml(&source),
+        #[cfg(feature = "ron")]
+        "ron" => from_ron(&source),
+        other => Err(ConfigError::UnsupportedFormat(other.to_string())),
+    }
+}
+
+#[cfg(all(test, any(feature = "json", feature = "toml", feature = "ron")))]
+mod tests {
+    use super::*;
+    #[cfg(any(feature = "json", feature = "toml"))]
+    use crate::types::Shape;
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn partial_json_falls_back_to_defaults() {
+        let opts =
+            from_json(r##"{ "particle_count": 120, "colors": ["#ff0000", "#0f0", "gold"] }"##)
+                .expect("parse");
+        assert_eq!(opts.particle_count, 120);
+        assert_eq!(
+            opts.colors,
+            vec![Color::RED, Color::GREEN, Color::new(255, 215, 0)]
+        );
+        assert_eq!(opts.ticks, ConfettiOptions::default().ticks);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_reads_nested_origin_and_shapes() {
+        use crate::types::Origin;
+
+        let opts =
+            from_toml("spread = 360.0\nshapes = [\"star\", \"circle\"]\n[origin]\ny = 0.25\n")
+                .expect("parse");
+        assert!((opts.spread - 360.0).abs() < f64::EPSILON);
+        assert_eq!(opts.shapes, vec![Shape::Star, Shape::Circle]);
+        assert_eq!(opts.origin, Origin { x: 0.5, y: 0.25 });
+    }
+
+    #[cfg(all(feature = "json", feature = "toml"))]
+    #[test]
+    fn path_shapes_parse_from_d_strings() {
+        let opts = from_toml("shapes = [\"star\", { path = \"M0 0 H4 V4 Z\" }]\n").expect("parse");
+        assert_eq!(opts.shapes[1], Shape::path("M0 0 H4 V4 Z").expect("path"));
+        let json = serde_json::to_string(&opts.shapes).expect("serialize");
+        assert_eq!(json, r#"["star",{"path":"M0 0 H4 V4 Z"}]"#);
+        let err = from_json(r#"{ "shapes": [{ "path": "M0 0 L" }] }"#).unwrap_err();
+        assert!(matches!(err, ConfigError::Parse(_)));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn text_shapes_default_font_and_scalar() {
+        let opts = from_json(r#"{ "shapes": [{ "text": { "text": "🎉" } }] }"#).expect("parse");
+        assert_eq!(opts.shapes, vec![Shape::text("🎉")]);
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn ron_round_trips() {
+        let opts = ConfettiOptions {
+            seed: Some(9),
+            colors: vec![Color::new(0x12, 0xab, 0xef)],
+            ..Default::default()
+        };
+        let text = ron::to_string(&opts).expect("serialize");
+        assert!(text.contains("\"#12abef\""));
+        let back = from_ron(&text).expect("parse");
+        assert_eq!(back.seed, Some(9));
+        assert_eq!(back.colors, opts.colors);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn invalid_color_is_an_error() {
+        let err = from_json(r#"{ "colors": ["red-ish"] }"#).unwrap_err();
+        assert!(matches!(err, ConfigError::Parse(_)));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn out_of_range_options_are_an_error() {
+        let err = from_toml("colors = []\n").unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::Invalid(OptionsError::EmptyColors)
+        ));
+    }
+
+    #[test]
+    fn unknown_extension_is_rejected() {
+        let path = std::env::temp_dir().join("glitterbomb-options.yaml");
+        std::fs::write(&path, "particle_count: 1").expect("write");
+        let err = load(&path).unwrap_err();
+        assert!(matches!(err, ConfigError::UnsupportedFormat(ext) if ext == "yaml"));
+        std::fs::remove_file(path).expect("cleanup");
+    }
+}
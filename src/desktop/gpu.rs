@@ -1,10 +1,12 @@
 //! GPU setup and rendering
 
-use super::particle::Particle;
-use crate::types::{ConfettiOptions, Origin};
+use super::renderer::Renderer;
+use crate::handle::{BurstTracker, Completion};
+use crate::presets::Preset;
+use crate::sim::Simulation;
 use std::sync::mpsc::Receiver;
 use std::sync::Arc;
-use wgpu::util::DeviceExt;
+use std::time::Instant;
 use winit::event::{Event, WindowEvent};
 // CHANGED: Imported EventLoopBuilder instead of EventLoop
 use winit::event_loop::{ControlFlow, EventLoopBuilder};
@@ -13,9 +15,13 @@ use winit::event_loop::{ControlFlow, EventLoopBuilder};
 use winit::platform::windows::EventLoopBuilderExtWindows;
 use winit::window::WindowBuilder;
 
+/// Logical size of the confetti window, in pixels.
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 600.0;
+
 pub enum Command {
-    Fireworks,
-    Celebration,
+    Spawn(Preset, Completion),
+    Reset,
 }
 
 pub fn run_event_loop(rx: Receiver<Command>) {
@@ -59,65 +65,21 @@ pub fn run_event_loop(rx: Receiver<Command>) {
         pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))
             .unwrap();
 
-    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-        label: Some("Particle Shader"),
-        source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!("shader.wgsl"))),
-    });
-
-    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-        label: Some("Pipeline Layout"),
-        bind_group_layouts: &[],
-        push_constant_ranges: &[],
-    });
-
     let format = surface.get_capabilities(&adapter).formats[0];
+    let mut renderer = Renderer::new(&device, format);
 
-    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-        label: Some("Render Pipeline"),
-        layout: Some(&layout),
-        vertex: wgpu::VertexState {
-            module: &shader,
-            entry_point: "vs_main",
-            buffers: &[wgpu::VertexBufferLayout {
-                array_stride: std::mem::size_of::<[f32; 6]>() as wgpu::BufferAddress,
-                step_mode: wgpu::VertexStepMode::Instance,
-                attributes: &[
-                    wgpu::VertexAttribute {
-                        offset: 0,
-                        shader_location: 0,
-                        format: wgpu::VertexFormat::Float32x2,
-                    },
-                    wgpu::VertexAttribute {
-                        offset: 8,
-                        shader_location: 1,
-                        format: wgpu::VertexFormat::Float32x4,
-                    },
-                ],
-            }],
-        },
-        fragment: Some(wgpu::FragmentState {
-            module: &shader,
-            entry_point: "fs_main",
-            targets: &[Some(wgpu::ColorTargetState {
-                format,
-                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                write_mask: wgpu::ColorWrites::ALL,
-            })],
-        }),
-        primitive: wgpu::PrimitiveState::default(),
-        depth_stencil: None,
-        multisample: wgpu::MultisampleState::default(),
-        multiview: None,
-    });
-
-    let mut particles: Vec<Particle> = Vec::new();
+    let mut sim = Simulation::new(rand::random::<f64>);
+    let mut tracker = BurstTracker::new();
+    let clock = Instant::now();
+    // Whether the last presented frame had particles on it.
+    let mut showing = false;
 
     event_loop
         .run(move |event, elwt| {
             elwt.set_control_flow(ControlFlow::Poll);
 
             if let Ok(cmd) = rx.try_recv() {
-                spawn(cmd, &mut particles);
+                spawn(cmd, &mut sim, &mut tracker);
                 window.request_redraw();
             }
 
@@ -130,10 +92,20 @@ pub fn run_event_loop(rx: Receiver<Command>) {
                     event: WindowEvent::RedrawRequested,
                     ..
                 } => {
-                    if particles.is_empty() {
+                    if !sim.is_empty() {
+                        sim.advance_to(clock.elapsed().as_secs_f64());
+                        tracker.sync(&mut sim);
+                    } else if !showing {
                         return;
                     }
-                    render(&surface, &device, &queue, &pipeline, &mut particles);
+                    // A reset empties the sim between frames; still present
+                    // one cleared frame so the last one does not linger.
+                    showing = !sim.is_empty();
+                    renderer.upload(&device, &queue, &sim.frame(), (WIDTH, HEIGHT));
+                    render(&surface, &device, &queue, &renderer);
+                    if showing {
+                        window.request_redraw();
+                    }
                 }
                 _ => {}
             }
@@ -141,78 +113,30 @@ pub fn run_event_loop(rx: Receiver<Command>) {
         .unwrap();
 }
 
-fn spawn(cmd: Command, particles: &mut Vec<Particle>) {
-    particles.clear();
-    let opts = match cmd {
-        Command::Fireworks => ConfettiOptions {
-            particle_count: 100,
-            spread: 360.0,
-            start_velocity: 30.0,
-            gravity: 0.5,
-            origin: Origin { x: 0.5, y: 0.5 },
-            ..Default::default()
-        },
-        Command::Celebration => ConfettiOptions {
-            particle_count: 50,
-            angle: 60.0,
-            spread: 55.0,
-            origin: Origin { x: 0.0, y: 0.6 },
-            ..Default::default()
-        },
-    };
-
-    for i in 0..opts.particle_count {
-        let color = opts.colors[rand::random::<usize>() % opts.colors.len()];
-        let mut p = Particle::new(&opts, 400.0, 300.0, color);
-
-        if matches!(cmd, Command::Celebration) {
-            if i % 2 == 0 {
-                p.x = 0.0;
-                p.vx = p.vx.abs();
-            } else {
-                p.x = 800.0;
-                p.vx = -p.vx.abs();
-            }
+fn spawn(cmd: Command, sim: &mut Simulation, tracker: &mut BurstTracker) {
+    match cmd {
+        Command::Spawn(preset, completion) => {
+            let ids = preset
+                .bursts()
+                .iter()
+                .map(|opts| sim.spawn(opts, WIDTH, HEIGHT))
+                .collect();
+            tracker.watch(ids, completion);
         }
-        particles.push(p);
+        Command::Reset => sim.clear(),
     }
+    tracker.sync(sim);
 }
 
 fn render(
     surface: &wgpu::Surface,
     device: &wgpu::Device,
     queue: &wgpu::Queue,
-    pipeline: &wgpu::RenderPipeline,
-    particles: &mut Vec<Particle>,
+    renderer: &Renderer,
 ) {
-    particles.retain_mut(|p| p.update(0.5, 0.0));
-    if particles.is_empty() {
-        return;
-    }
-
     let output = surface.get_current_texture().unwrap();
     let view = output.texture.create_view(&Default::default());
 
-    let data: Vec<[f32; 6]> = particles
-        .iter()
-        .map(|p| [p.x, p.y, p.color[0], p.color[1], p.color[2], p.color[3]])
-        .collect();
-
-    let instance_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Instance Buffer"),
-        contents: bytemuck::cast_slice(&data),
-        usage: wgpu::BufferUsages::VERTEX,
-    });
-
-    let verts: &[f32] = &[
-        -1.0, -1.0, 1.0, -1.0, 1.0, 1.0, -1.0, -1.0, 1.0, 1.0, -1.0, 1.0,
-    ];
-    let vert_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("Vertex Buffer"),
-        contents: bytemuck::cast_slice(verts),
-        usage: wgpu::BufferUsages::VERTEX,
-    });
-
     let mut encoder = device.create_command_encoder(&Default::default());
     {
         let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
@@ -234,10 +158,7 @@ fn render(
             timestamp_writes: None,
             occlusion_query_set: None,
         });
-        pass.set_pipeline(pipeline);
-        pass.set_vertex_buffer(0, vert_buf.slice(..));
-        pass.set_vertex_buffer(1, instance_buf.slice(..));
-        pass.draw(0..6, 0..particles.len() as u32);
+        renderer.draw(&mut pass);
     }
     queue.submit(std::iter::once(encoder.finish()));
     output.present();
@@ -0,0 +1,228 @@
+//! Triangle meshes for simulation particles
+
+use crate::sim::Particle;
+use crate::types::{ImageShape, Shape};
+use lyon_tessellation::math::point;
+use lyon_tessellation::path::Path;
+use lyon_tessellation::{BuffersBuilder, FillOptions, FillTessellator, FillVertex, VertexBuffers};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::mem::{self, Discriminant};
+use std::rc::Rc;
+
+/// Per-vertex data: pixel position followed by RGBA color.
+pub type Vertex = [f32; 6];
+
+/// Per-vertex sprite data: pixel position, texture coordinate and opacity.
+pub type SpriteVertex = [f32; 5];
+
+/// Textured triangles that all sample the same image.
+pub struct Sprites {
+    pub image: ImageShape,
+    pub verts: Vec<SpriteVertex>,
+}
+
+/// Flat triangle list, three points per triangle.
+type Triangles = Rc<Vec<(f64, f64)>>;
+
+/// Shape kind plus path source, empty for built-in polygons.
+type Key = (Discriminant<Shape>, String);
+
+thread_local! {
+    /// Unit-box triangles for each path shape and built-in polygon.
+    static TESSELLATIONS: RefCell<HashMap<Key, Triangles>> = RefCell::new(HashMap::new());
+}
+
+/// Triangulate every particle: squares, circles and text as a fan around
+/// their position, paths and built-in polygons from their cached
+/// tessellation and ribbons as a strip. Image particles are left to
+/// [`sprites`].
+#[allow(clippy::cast_possible_truncation)]
+pub fn build(particles: &[Particle]) -> Vec<Vertex> {
+    let mut verts = Vec::new();
+    for p in particles.iter().filter(|p| sprite(p).is_none()) {
+        let c = p.color();
+        let color = [
+            f32::from(c.r) / 255.0,
+            f32::from(c.g) / 255.0,
+            f32::from(c.b) / 255.0,
+            (p.opacity() * c.alpha()) as f32,
+        ];
+        let mut push = |(x, y): (f64, f64)| {
+            verts.push([x as f32, y as f32, color[0], color[1], color[2], color[3]]);
+        };
+        if let Some(triangles) = tessellation(p.shape()) {
+            let m = p.unit_transform();
+            for &(x, y) in triangles.iter() {
+                push((m[0] * x + m[2] * y + m[4], m[1] * x + m[3] * y + m[5]));
+            }
+            continue;
+        }
+        if let Some(sections) = p.ribbon_sections() {
+            for pair in sections.windows(2) {
+                let ([a, b], [c, d]) = (pair[0], pair[1]);
+                for v in [a, b, c, b, d, c] {
+                    push(v);
+                }
+            }
+            continue;
+        }
+        let center = p.position();
+        let outline = p.outline();
+        for (i, &a) in outline.iter().enumerate() {
+            let b = outline[(i + 1) % outline.len()];
+            for v in [center, a, b] {
+                push(v);
+            }
+        }
+    }
+    verts
+}
+
+/// Two textured triangles per image particle, batched by image.
+#[allow(clippy::cast_possible_truncation)]
+pub fn sprites(particles: &[Particle]) -> Vec<Sprites> {
+    const UNIT: [(f64, f64); 4] = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+    let mut batches: Vec<Sprites> = Vec::new();
+    for p in particles {
+        let Some(image) = sprite(p) else {
+            continue;
+        };
+        let index = if let Some(index) = batches.iter().position(|b| b.image == *image) {
+            index
+        } else {
+            batches.push(Sprites {
+                image: image.clone(),
+                verts: Vec::new(),
+            });
+            batches.len() - 1
+        };
+        let m = p.unit_transform();
+        let (hw, hh) = image.half_extents();
+        let alpha = p.opacity() as f32;
+        let corner = |i: usize| {
+            let (sx, sy) = UNIT[i];
+            let (x, y) = (sx * hw, sy * hh);
+            [
+                (m[0] * x + m[2] * y + m[4]) as f32,
+                (m[1] * x + m[3] * y + m[5]) as f32,
+                (sx as f32 + 1.0) * 0.5,
+                (sy as f32 + 1.0) * 0.5,
+                alpha,
+            ]
+        };
+        batches[index].verts.extend([0, 1, 2, 0, 2, 3].map(corner));
+    }
+    batches
+}
+
+/// The image drawn for `p`, if it is a sprite with pixels to upload.
+fn sprite(p: &Particle) -> Option<&ImageShape> {
+    match p.shape() {
+        Shape::Image(image) if image.pixels().is_some() => Some(image),
+        _ => None,
+    }
+}
+
+/// Triangle list covering a path or built-in polygon in unit-box
+/// coordinates, or `None` for shapes drawn another way.
+fn tessellation(shape: &Shape) -> Option<Triangles> {
+    let key = match shape {
+        Shape::Path(path) => path.source().to_string(),
+        _ if shape.unit_outline().is_some() => String::new(),
+        _ => return None,
+    };
+    let key = (mem::discriminant(shape), key);
+    let triangles = TESSELLATIONS.with(|cache| {
+        Rc::clone(cache.borrow_mut().entry(key).or_insert_with(|| {
+            Rc::new(match shape {
+                Shape::Path(path) => tessellate(path.subpaths()),
+                _ => tessellate(&[shape.unit_outline().unwrap_or_default()]),
+            })
+        }))
+    });
+    Some(triangles)
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn tessellate(subpaths: &[Vec<(f64, f64)>]) -> Vec<(f64, f64)> {
+    let mut builder = Path::builder();
+    for sub in subpaths {
+        let Some((&(x, y), rest)) = sub.split_first() else {
+            continue;
+        };
+        builder.begin(point(x as f32, y as f32));
+        for &(x, y) in rest {
+            builder.line_to(point(x as f32, y as f32));
+        }
+        builder.end(true);
+    }
+    let mut buffers: VertexBuffers<(f64, f64), u32> = VertexBuffers::new();
+    let result = FillTessellator::new().tessellate_path(
+        &builder.build(),
+        &FillOptions::non_zero(),
+        &mut BuffersBuilder::new(&mut buffers, |v: FillVertex| {
+            let p = v.position();
+            (f64::from(p.x), f64::from(p.y))
+        }),
+    );
+    if result.is_err() {
+        return Vec::new();
+    }
+    buffers
+        .indices
+        .iter()
+        .map(|&i| buffers.vertices[i as usize])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PathShape;
+
+    #[test]
+    fn tessellation_respects_holes() {
+        let frame = PathShape::parse("M0 0 H4 V4 H0 Z M1 1 V3 H3 V1 Z").expect("path");
+        let triangles = tessellate(frame.subpaths());
+        assert_eq!(triangles.len() % 3, 0);
+        let area: f64 = triangles
+            .chunks(3)
+            .map(|t| {
+                let (a, b, c) = (t[0], t[1], t[2]);
+                ((b.0 - a.0) * (c.1 - a.1) - (c.0 - a.0) * (b.1 - a.1)).abs() / 2.0
+            })
+            .sum();
+        // 16 - 4 square units, scaled by 1/4 per side into the unit box.
+        assert!((area - 0.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn built_in_polygons_are_tessellated() {
+        let triangles = tessellation(&Shape::Crescent).expect("crescent");
+        assert_eq!(triangles.len() % 3, 0);
+        assert!(!triangles.is_empty());
+        assert!(tessellation(&Shape::Square).is_none());
+    }
+
+    #[test]
+    fn image_particles_become_sprites() {
+        use crate::sim::Simulation;
+        use crate::types::ConfettiOptions;
+
+        let image = Shape::image(1, 1, vec![255; 4]).expect("image");
+        let opts = ConfettiOptions {
+            particle_count: 3,
+            shapes: vec![image],
+            seed: Some(1),
+            ..Default::default()
+        };
+        let mut sim = Simulation::new(|| 0.5);
+        sim.spawn(&opts, 100.0, 100.0);
+        let particles = sim.frame();
+        assert!(build(&particles).is_empty());
+        let batches = sprites(&particles);
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].verts.len(), 18);
+    }
+}
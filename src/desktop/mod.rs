@@ -1,32 +1,71 @@
 //! Desktop implementation using wgpu + winit
 
 mod gpu;
-pub mod particle;
+mod mesh;
+mod renderer;
+mod sprite;
 
 use std::sync::mpsc::{channel, Sender};
 use std::sync::Mutex;
 use std::thread;
 
+use crate::handle::BurstHandle;
+use crate::presets::{self, Preset};
 pub use crate::types::{Color, ConfettiOptions, Origin};
+pub use renderer::Renderer;
 
 static SENDER: Mutex<Option<Sender<gpu::Command>>> = Mutex::new(None);
 
 pub fn fireworks() {
-    send(gpu::Command::Fireworks);
+    drop(fire(&Preset::fireworks()));
 }
 
 pub fn celebration() {
-    send(gpu::Command::Celebration);
+    drop(fire(&Preset::celebration()));
+}
+
+pub fn cannon() {
+    drop(fire(&Preset::cannon()));
+}
+
+pub fn snow() {
+    drop(fire(&Preset::snow()));
+}
+
+#[must_use]
+pub fn confetti(opts: &ConfettiOptions) -> BurstHandle {
+    fire(&Preset::from(opts.clone()))
+}
+
+/// Fire every burst of a preset, with one handle for all of them.
+#[must_use]
+pub fn fire(preset: &Preset) -> BurstHandle {
+    let (handle, completion) = BurstHandle::new();
+    send(gpu::Command::Spawn(preset.clone(), completion));
+    handle
+}
+
+/// Fire the preset registered or built in under `name`.
+///
+/// Returns `false` if no such preset exists.
+#[must_use]
+pub fn fire_preset(name: &str) -> bool {
+    presets::preset(name).map(|p| fire(&p)).is_some()
 }
 
-pub fn cannon() {}
-pub fn snow() {}
-pub fn confetti(_: &ConfettiOptions) {}
 pub fn confetti_on_canvas(_: &(), _: &ConfettiOptions) {}
-pub fn reset() {}
+
+pub fn reset() {
+    deliver(gpu::Command::Reset);
+}
 
 fn send(cmd: gpu::Command) {
     init();
+    deliver(cmd);
+}
+
+/// Forward a command to the event loop thread, if it is running.
+fn deliver(cmd: gpu::Command) {
     if let Ok(s) = SENDER.lock() {
         if let Some(tx) = s.as_ref() {
             let _ = tx.send(cmd);
@@ -0,0 +1,149 @@
+//! Records confetti draws into a caller's wgpu render pass.
+
+use super::mesh;
+use super::sprite::{Batch, SpritePipeline};
+use crate::sim::Particle;
+use crate::system::ConfettiSystem;
+use wgpu::util::DeviceExt;
+
+/// Pipelines and per-frame buffers for drawing particles with wgpu.
+///
+/// Call [`prepare`](Self::prepare) before beginning the render pass and
+/// [`draw`](Self::draw) inside it, at the point in the host's z-order where
+/// confetti belongs. Nothing is cleared.
+pub struct Renderer {
+    pipeline: wgpu::RenderPipeline,
+    sprites: SpritePipeline,
+    solids: Option<(wgpu::Buffer, u32)>,
+    batches: Vec<Batch>,
+}
+
+impl Renderer {
+    /// Pipelines targeting color attachments of `format`.
+    #[must_use]
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Particle Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!(
+                "shader.wgsl"
+            ))),
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Pipeline Layout"),
+            bind_group_layouts: &[],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Render Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<mesh::Vertex>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &[
+                        wgpu::VertexAttribute {
+                            offset: 0,
+                            shader_location: 0,
+                            format: wgpu::VertexFormat::Float32x2,
+                        },
+                        wgpu::VertexAttribute {
+                            offset: 8,
+                            shader_location: 1,
+                            format: wgpu::VertexFormat::Float32x4,
+                        },
+                    ],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self {
+            pipeline,
+            sprites: SpritePipeline::new(device, format),
+            solids: None,
+            batches: Vec::new(),
+        }
+    }
+
+    /// Upload the current frame of `system`, mapping its surface size onto
+    /// the whole render target.
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, system: &ConfettiSystem) {
+        self.upload(device, queue, &system.frame(), system.size());
+    }
+
+    /// Record draws for the last prepared frame.
+    pub fn draw<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        // Empty buffers cannot be bound, e.g. when every particle is a sprite.
+        if let Some((buffer, count)) = &self.solids {
+            pass.set_pipeline(&self.pipeline);
+            pass.set_vertex_buffer(0, buffer.slice(..));
+            pass.draw(0..*count, 0..1);
+        }
+        self.sprites.draw(pass, &self.batches);
+    }
+
+    /// Upload `particles` laid out on a `size` surface.
+    pub(super) fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        particles: &[Particle],
+        size: (f64, f64),
+    ) {
+        let mut verts = mesh::build(particles);
+        let mut sprites = mesh::sprites(particles);
+        to_clip(&mut verts, size);
+        for batch in &mut sprites {
+            to_clip(&mut batch.verts, size);
+        }
+        self.batches = self.sprites.prepare(device, queue, &sprites);
+        let count = u32::try_from(verts.len()).unwrap_or(0);
+        self.solids = (count > 0).then(|| {
+            let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Vertex Buffer"),
+                contents: bytemuck::cast_slice(&verts),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+            (buffer, count)
+        });
+    }
+}
+
+/// Map pixel positions, the first two floats of each vertex, on a `size`
+/// surface to clip space.
+#[allow(clippy::cast_possible_truncation)]
+fn to_clip<const N: usize>(verts: &mut [[f32; N]], (width, height): (f64, f64)) {
+    let (sx, sy) = ((2.0 / width) as f32, (2.0 / height) as f32);
+    for v in verts {
+        v[0] = v[0] * sx - 1.0;
+        v[1] = 1.0 - v[1] * sy;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corners_map_to_clip_space() {
+        let mut verts = [[0.0, 0.0, 1.0], [800.0, 600.0, 1.0], [400.0, 150.0, 1.0]];
+        to_clip(&mut verts, (800.0, 600.0));
+        assert_eq!(verts, [[-1.0, 1.0, 1.0], [1.0, -1.0, 1.0], [0.0, 0.5, 1.0]]);
+    }
+}
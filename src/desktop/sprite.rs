@@ -0,0 +1,208 @@
+//! Textured pipeline for image particles
+
+use super::mesh::{SpriteVertex, Sprites};
+use std::collections::HashMap;
+use wgpu::util::DeviceExt;
+
+/// Render pipeline plus one uploaded texture per live image.
+pub struct SpritePipeline {
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    textures: HashMap<u64, wgpu::BindGroup>,
+}
+
+/// A batch ready to draw: its image id, vertex buffer and vertex count.
+pub struct Batch {
+    id: u64,
+    buffer: wgpu::Buffer,
+    count: u32,
+}
+
+impl SpritePipeline {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Sprite Shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!(
+                "sprite.wgsl"
+            ))),
+        });
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Sprite Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Sprite Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Sprite Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_sprite",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<SpriteVertex>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &[
+                        wgpu::VertexAttribute {
+                            offset: 0,
+                            shader_location: 0,
+                            format: wgpu::VertexFormat::Float32x2,
+                        },
+                        wgpu::VertexAttribute {
+                            offset: 8,
+                            shader_location: 1,
+                            format: wgpu::VertexFormat::Float32x2,
+                        },
+                        wgpu::VertexAttribute {
+                            offset: 16,
+                            shader_location: 2,
+                            format: wgpu::VertexFormat::Float32,
+                        },
+                    ],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_sprite",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Sprite Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            pipeline,
+            layout,
+            sampler,
+            textures: HashMap::new(),
+        }
+    }
+
+    /// Upload vertex buffers for this frame, and textures for images not
+    /// seen before. Textures of images no longer on screen are released.
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        sprites: &[Sprites],
+    ) -> Vec<Batch> {
+        self.textures
+            .retain(|id, _| sprites.iter().any(|s| s.image.id() == *id));
+        let mut batches = Vec::with_capacity(sprites.len());
+        for sprite in sprites {
+            let id = sprite.image.id();
+            let Ok(count) = u32::try_from(sprite.verts.len()) else {
+                continue;
+            };
+            if !self.textures.contains_key(&id) {
+                let Some(bind_group) = self.upload(device, queue, sprite) else {
+                    continue;
+                };
+                self.textures.insert(id, bind_group);
+            }
+            batches.push(Batch {
+                id,
+                buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Sprite Vertex Buffer"),
+                    contents: bytemuck::cast_slice(&sprite.verts),
+                    usage: wgpu::BufferUsages::VERTEX,
+                }),
+                count,
+            });
+        }
+        batches
+    }
+
+    /// Record draws for batches returned by [`prepare`](Self::prepare).
+    pub fn draw<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, batches: &'a [Batch]) {
+        pass.set_pipeline(&self.pipeline);
+        for batch in batches {
+            let Some(bind_group) = self.textures.get(&batch.id) else {
+                continue;
+            };
+            pass.set_bind_group(0, bind_group, &[]);
+            pass.set_vertex_buffer(0, batch.buffer.slice(..));
+            pass.draw(0..batch.count, 0..1);
+        }
+    }
+
+    fn upload(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        sprite: &Sprites,
+    ) -> Option<wgpu::BindGroup> {
+        let pixels = sprite.image.pixels()?;
+        let (width, height) = sprite.image.size();
+        let texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("Sprite Texture"),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            pixels,
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Sprite Bind Group"),
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        }))
+    }
+}
@@ -0,0 +1,27 @@
+struct SpriteOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) alpha: f32,
+};
+
+@group(0) @binding(0) var sprite: texture_2d<f32>;
+@group(0) @binding(1) var sprite_sampler: sampler;
+
+@vertex
+fn vs_sprite(
+    @location(0) pos: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) alpha: f32,
+) -> SpriteOutput {
+    var out: SpriteOutput;
+    out.clip_position = vec4<f32>(pos, 0.0, 1.0);
+    out.uv = uv;
+    out.alpha = alpha;
+    return out;
+}
+
+@fragment
+fn fs_sprite(in: SpriteOutput) -> @location(0) vec4<f32> {
+    let texel = textureSample(sprite, sprite_sampler, in.uv);
+    return vec4<f32>(texel.rgb, texel.a * in.alpha);
+}
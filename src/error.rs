@@ -0,0 +1,43 @@
+//! Errors from firing confetti in the browser.
+
+use std::fmt;
+
+/// Why a burst could not be started.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GlitterbombError {
+    /// There is no global `window`, e.g. outside a browser main thread.
+    NoWindow,
+    /// The window has no `document`.
+    NoDocument,
+    /// The document has no `<body>` to attach the overlay canvas to.
+    NoBody,
+    /// A `<canvas>` element could not be created.
+    CanvasUnavailable,
+    /// The canvas refused to hand out a 2D rendering context.
+    ContextUnavailable,
+    /// `requestAnimationFrame` could not be scheduled.
+    AnimationFrameUnavailable,
+    /// The overlay could not be handed to a Web Worker, or the worker could
+    /// not be started.
+    WorkerUnavailable,
+    /// The options cannot be sent to the worker, e.g. because they use image
+    /// shapes.
+    NotSendable,
+}
+
+impl fmt::Display for GlitterbombError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::NoWindow => "no global window",
+            Self::NoDocument => "window has no document",
+            Self::NoBody => "document has no body",
+            Self::CanvasUnavailable => "could not create a canvas element",
+            Self::ContextUnavailable => "could not get a 2D canvas context",
+            Self::AnimationFrameUnavailable => "could not request an animation frame",
+            Self::WorkerUnavailable => "could not start the confetti worker",
+            Self::NotSendable => "options cannot be sent to the confetti worker",
+        })
+    }
+}
+
+impl std::error::Error for GlitterbombError {}
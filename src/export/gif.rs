@@ -0,0 +1,125 @@
+//! Animated GIF export.
+
+use super::palette::Palette;
+use super::{ExportError, ExportSettings};
+use crate::types::ConfettiOptions;
+use gif::{DisposalMethod, Encoder, EncodingError, Frame, Repeat};
+use std::borrow::Cow;
+use std::io::Write;
+
+/// Run `bursts` to completion offscreen and encode them as a looping GIF.
+///
+/// The palette is derived from the bursts' `colors`. With a background, each
+/// color gets fade steps blended over it; without one, the GIF is transparent
+/// and fading particles are cut off at half opacity, since GIF has no partial
+/// transparency.
+///
+/// # Errors
+///
+/// Returns [`ExportError`] if the settings are out of range for GIF or the
+/// output cannot be written.
+#[allow(clippy::cast_possible_truncation)]
+pub fn export_gif<W: Write>(
+    writer: W,
+    bursts: &[ConfettiOptions],
+    settings: &ExportSettings,
+) -> Result<(), ExportError> {
+    settings.validate(u32::from(u16::MAX))?;
+    let (width, height) = (settings.width as u16, settings.height as u16);
+    let mut palette = Palette::for_bursts(bursts, settings.background);
+    let mut encoder = Encoder::new(writer, width, height, &palette.rgb_bytes())?;
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    for (i, frame) in settings.frames(bursts).enumerate() {
+        let indices: Vec<u8> = frame
+            .pixels()
+            .chunks_exact(4)
+            .map(|px| palette.index_of([px[0], px[1], px[2], px[3]]))
+            .collect();
+        encoder.write_frame(&Frame {
+            width,
+            height,
+            delay: delay(i, settings.fps),
+            dispose: DisposalMethod::Background,
+            transparent: palette.transparent(),
+            buffer: Cow::Owned(indices),
+            ..Frame::default()
+        })?;
+    }
+    Ok(())
+}
+
+/// Delay of frame `i` in hundredths of a second. Rounding is spread across
+/// frames so the total duration matches `fps` even though GIF timing is
+/// coarse.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn delay(i: usize, fps: f64) -> u16 {
+    let at = |n: usize| (n as f64 * 100.0 / fps).round() as u64;
+    (at(i + 1) - at(i)).min(u64::from(u16::MAX)) as u16
+}
+
+impl From<EncodingError> for ExportError {
+    fn from(err: EncodingError) -> Self {
+        match err {
+            EncodingError::Io(err) => Self::Io(err),
+            EncodingError::Format(err) => Self::Encode(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Color;
+
+    fn burst() -> ConfettiOptions {
+        ConfettiOptions {
+            seed: Some(5),
+            ticks: 30,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn delays_add_up_to_real_time() {
+        let total: u32 = (0..30).map(|i| u32::from(delay(i, 30.0))).sum();
+        assert_eq!(total, 100);
+    }
+
+    #[test]
+    fn writes_a_decodable_gif() {
+        let settings = ExportSettings {
+            width: 64,
+            height: 48,
+            background: Some(Color::WHITE),
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        export_gif(&mut out, &[burst()], &settings).expect("export");
+        assert!(out.starts_with(b"GIF89a"));
+
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(out.as_slice())
+            .expect("decode");
+        assert_eq!((decoder.width(), decoder.height()), (64, 48));
+        let mut frames = 0;
+        while decoder.read_next_frame().expect("frame").is_some() {
+            frames += 1;
+        }
+        assert_eq!(frames, 14);
+    }
+
+    #[test]
+    fn rejects_zero_size() {
+        let settings = ExportSettings {
+            width: 0,
+            ..Default::default()
+        };
+        let err = export_gif(Vec::new(), &[burst()], &settings).unwrap_err();
+        assert!(matches!(err, ExportError::InvalidSize { width: 0, .. }));
+    }
+}
@@ -0,0 +1,476 @@
+//! Lottie (bodymovin) JSON export.
+//!
+//! Each particle becomes one shape layer whose transform is keyframed once
+//! per output frame. Every shape is expressed as a base shape plus rotation
+//! and (possibly negative) scale, which reproduces the tilt and 3D flip.
+//! Ribbons change shape every frame, so their outline is keyframed directly.
+
+use super::{ExportError, ExportSettings};
+use crate::sim::Particle;
+use crate::types::{Color, ConfettiOptions, Shape};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// Side of the unscaled square and circle, in pixels.
+const BASE: f64 = 20.0;
+
+/// Transform of one particle at one frame.
+struct Sample {
+    position: (f64, f64),
+    rotation: f64,
+    scale: (f64, f64),
+    opacity: f64,
+    color: Color,
+    /// Pixel-space outline, for shapes keyframed point by point.
+    outline: Vec<(f64, f64)>,
+}
+
+/// Every sample recorded for one particle.
+struct Track {
+    shape: Shape,
+    first_frame: u32,
+    samples: Vec<Sample>,
+}
+
+/// Run `bursts` to completion and write them as a Lottie JSON document.
+///
+/// `settings.supersample` is ignored, since Lottie is vector output.
+///
+/// # Errors
+///
+/// Returns [`ExportError`] if the settings are invalid or the output cannot
+/// be written.
+pub fn export_lottie<W: Write>(
+    writer: W,
+    bursts: &[ConfettiOptions],
+    settings: &ExportSettings,
+) -> Result<(), ExportError> {
+    let document = lottie_document(bursts, settings)?;
+    serde_json::to_writer(writer, &document).map_err(|err| match err.io_error_kind() {
+        Some(kind) => ExportError::Io(kind.into()),
+        None => ExportError::Encode(err.to_string()),
+    })
+}
+
+/// Build the Lottie document for `bursts` without serializing it.
+///
+/// # Errors
+///
+/// Returns [`ExportError`] if the settings are invalid.
+pub fn lottie_document(
+    bursts: &[ConfettiOptions],
+    settings: &ExportSettings,
+) -> Result<Value, ExportError> {
+    settings.validate(u32::MAX)?;
+    let (tracks, frames) = record(bursts, settings);
+    // Lottie draws the first layer on top, canvas draws the last particle on top.
+    let mut layers: Vec<Value> = tracks
+        .values()
+        .rev()
+        .enumerate()
+        .map(|(i, track)| layer(i + 1, track))
+        .collect();
+    if let Some(color) = settings.background {
+        layers.push(background(layers.len() + 1, color, settings, frames));
+    }
+    Ok(json!({
+        "v": "5.7.4",
+        "nm": "glitterbomb",
+        "fr": settings.fps,
+        "ip": 0,
+        "op": frames,
+        "w": settings.width,
+        "h": settings.height,
+        "ddd": 0,
+        "assets": [],
+        "layers": layers,
+    }))
+}
+
+/// Simulate the bursts, sampling every particle once per output frame.
+fn record(bursts: &[ConfettiOptions], settings: &ExportSettings) -> (BTreeMap<u64, Track>, u32) {
+    let mut source = settings.frames(bursts);
+    let mut tracks = BTreeMap::new();
+    let mut frame = 0;
+    while source.advance() {
+        for p in source.particles() {
+            let track = tracks.entry(p.id()).or_insert_with(|| Track {
+                shape: p.shape().clone(),
+                first_frame: frame,
+                samples: Vec::new(),
+            });
+            track.samples.push(sample(&p));
+        }
+        frame += 1;
+    }
+    (tracks, frame)
+}
+
+fn sample(p: &Particle) -> Sample {
+    let opacity = p.opacity() * 100.0;
+    let color = p.color();
+    match p.shape() {
+        Shape::Circle => {
+            let (rx, ry, rotation) = p.ellipse();
+            Sample {
+                position: p.position(),
+                rotation: rotation.to_degrees(),
+                scale: (2.0 * rx / BASE * 100.0, 2.0 * ry / BASE * 100.0),
+                opacity,
+                color,
+                outline: Vec::new(),
+            }
+        }
+        Shape::Ribbon => Sample {
+            position: (0.0, 0.0),
+            rotation: 0.0,
+            scale: (100.0, 100.0),
+            opacity,
+            color,
+            outline: p.outline(),
+        },
+        _ => {
+            let [c0, c1, c2, c3] = p.corners();
+            let centre = ((c0.0 + c2.0) * 0.5, (c0.1 + c2.1) * 0.5);
+            let u = (c1.0 - c0.0, c1.1 - c0.1);
+            let v = (c3.0 - c0.0, c3.1 - c0.1);
+            let width = u.0.hypot(u.1).max(f64::EPSILON);
+            // Signed height perpendicular to `u`; negative when flipped.
+            let height = (u.0 * v.1 - u.1 * v.0) / width;
+            Sample {
+                position: centre,
+                rotation: u.1.atan2(u.0).to_degrees(),
+                scale: (width / BASE * 100.0, height / BASE * 100.0),
+                opacity,
+                color,
+                outline: Vec::new(),
+            }
+        }
+    }
+}
+
+fn layer(index: usize, track: &Track) -> Value {
+    let first = track.first_frame;
+    let keyframes = |value: &dyn Fn(&Sample) -> Value| animated(first, &track.samples, value);
+    json!({
+        "ddd": 0,
+        "ind": index,
+        "ty": 4,
+        "nm": format!("particle {index}"),
+        "sr": 1,
+        "ks": {
+            "o": keyframes(&|s| json!([s.opacity])),
+            "r": keyframes(&|s| json!([s.rotation])),
+            "p": keyframes(&|s| json!([s.position.0, s.position.1, 0.0])),
+            "a": fixed(json!([0.0, 0.0, 0.0])),
+            "s": keyframes(&|s| json!([s.scale.0, s.scale.1, 100.0])),
+        },
+        "ao": 0,
+        "shapes": [{
+            "ty": "gr",
+            "nm": "shape",
+            "it": items(track),
+        }],
+        "ip": first,
+        "op": first + u32::try_from(track.samples.len()).unwrap_or(u32::MAX),
+        "st": 0,
+        "bm": 0,
+    })
+}
+
+/// A property keyframed once per sample, interpolated linearly.
+fn animated(first: u32, samples: &[Sample], value: &dyn Fn(&Sample) -> Value) -> Value {
+    let keys: Vec<Value> = (first..)
+        .zip(samples)
+        .map(|(t, s)| {
+            json!({
+                "t": t,
+                "s": value(s),
+                "i": { "x": [1.0], "y": [1.0] },
+                "o": { "x": [0.0], "y": [0.0] },
+            })
+        })
+        .collect();
+    json!({ "a": 1, "k": keys })
+}
+
+fn fixed(value: Value) -> Value {
+    let mut property = json!({ "a": 0 });
+    property["k"] = value;
+    property
+}
+
+/// Shape group contents: the geometry, then its fill and transform.
+fn items(track: &Track) -> Vec<Value> {
+    let mut items = geometry(track);
+    items.push(fill(track));
+    items.push(group_transform());
+    items
+}
+
+/// Closed straight-edged path, in the same units as the base square.
+fn polyline(points: &[(f64, f64)]) -> Value {
+    let vertices: Vec<[f64; 2]> = points.iter().map(|p| [p.0 * BASE, p.1 * BASE]).collect();
+    let tangents = vec![[0.0, 0.0]; vertices.len()];
+    json!({
+        "ty": "sh",
+        "ks": fixed(json!({ "c": true, "v": vertices, "i": tangents, "o": tangents })),
+    })
+}
+
+/// Closed path keyframed from each sample's pixel-space outline.
+fn animated_outline(track: &Track) -> Value {
+    let keys: Vec<Value> = (track.first_frame..)
+        .zip(&track.samples)
+        .map(|(t, s)| {
+            let vertices: Vec<[f64; 2]> = s.outline.iter().map(|p| [p.0, p.1]).collect();
+            let tangents = vec![[0.0, 0.0]; vertices.len()];
+            json!({
+                "t": t,
+                "s": [{ "c": true, "v": vertices, "i": tangents, "o": tangents }],
+                "i": { "x": [1.0], "y": [1.0] },
+                "o": { "x": [0.0], "y": [0.0] },
+            })
+        })
+        .collect();
+    json!({ "ty": "sh", "ks": { "a": 1, "k": keys } })
+}
+
+fn geometry(track: &Track) -> Vec<Value> {
+    let centre = fixed(json!([0.0, 0.0]));
+    let primitive = match &track.shape {
+        Shape::Path(path) => return path.subpaths().iter().map(|s| polyline(s)).collect(),
+        Shape::Ribbon => return vec![animated_outline(track)],
+        Shape::Square => json!({
+            "ty": "rc", "p": centre, "s": fixed(json!([BASE, BASE])), "r": fixed(json!(0)),
+        }),
+        // Lottie has no portable emoji rendering, so text becomes its box.
+        Shape::Text { scalar, .. } => json!({
+            "ty": "rc", "p": centre, "s": fixed(json!([BASE * scalar, BASE * scalar])),
+            "r": fixed(json!(0)),
+        }),
+        // Image assets are not embedded; sprites become their rectangle.
+        Shape::Image(image) => {
+            let (hw, hh) = image.half_extents();
+            json!({
+                "ty": "rc", "p": centre, "s": fixed(json!([2.0 * hw * BASE, 2.0 * hh * BASE])),
+                "r": fixed(json!(0)),
+            })
+        }
+        Shape::Circle => json!({
+            "ty": "el", "p": centre, "s": fixed(json!([BASE, BASE])),
+        }),
+        // Radii match the unit outline, in units of the base square.
+        Shape::Star => json!({
+            "ty": "sr", "sy": 1, "p": centre,
+            "pt": fixed(json!(5)), "r": fixed(json!(0)),
+            "ir": fixed(json!(0.4 * BASE)), "is": fixed(json!(0)),
+            "or": fixed(json!(0.8 * BASE)), "os": fixed(json!(0)),
+        }),
+        _ => {
+            return track
+                .shape
+                .unit_outline()
+                .iter()
+                .map(|o| polyline(o))
+                .collect()
+        }
+    };
+    vec![primitive]
+}
+
+/// Solid fill, keyframed only when a gradient changes the color over time.
+fn fill(track: &Track) -> Value {
+    let first = track
+        .samples
+        .first()
+        .map_or(Color::TRANSPARENT, |s| s.color);
+    let (color, opacity) = if track.samples.iter().all(|s| s.color == first) {
+        (
+            fixed(json!(unit_rgba(first))),
+            fixed(json!(first.alpha() * 100.0)),
+        )
+    } else {
+        let keyframes =
+            |value: &dyn Fn(&Sample) -> Value| animated(track.first_frame, &track.samples, value);
+        (
+            keyframes(&|s| json!(unit_rgba(s.color))),
+            keyframes(&|s| json!([s.color.alpha() * 100.0])),
+        )
+    };
+    json!({ "ty": "fl", "c": color, "o": opacity, "r": 1 })
+}
+
+fn group_transform() -> Value {
+    json!({
+        "ty": "tr",
+        "p": fixed(json!([0.0, 0.0])),
+        "a": fixed(json!([0.0, 0.0])),
+        "s": fixed(json!([100.0, 100.0])),
+        "r": fixed(json!(0)),
+        "o": fixed(json!(100)),
+    })
+}
+
+fn background(index: usize, color: Color, settings: &ExportSettings, frames: u32) -> Value {
+    json!({
+        "ddd": 0,
+        "ind": index,
+        "ty": 1,
+        "nm": "background",
+        "sc": format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b),
+        "sw": settings.width,
+        "sh": settings.height,
+        "ks": {
+            "o": fixed(json!(100)),
+            "r": fixed(json!(0)),
+            "p": fixed(json!([0.0, 0.0, 0.0])),
+            "a": fixed(json!([0.0, 0.0, 0.0])),
+            "s": fixed(json!([100.0, 100.0, 100.0])),
+        },
+        "ip": 0,
+        "op": frames,
+        "st": 0,
+        "bm": 0,
+    })
+}
+
+fn unit_rgba(color: Color) -> [f64; 4] {
+    [
+        f64::from(color.r) / 255.0,
+        f64::from(color.g) / 255.0,
+        f64::from(color.b) / 255.0,
+        1.0,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn burst(shapes: Vec<Shape>) -> ConfettiOptions {
+        ConfettiOptions {
+            seed: Some(3),
+            particle_count: 6,
+            ticks: 12,
+            shapes,
+            ..Default::default()
+        }
+    }
+
+    fn settings() -> ExportSettings {
+        ExportSettings {
+            fps: 60.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn one_layer_per_particle() {
+        let doc = lottie_document(&[burst(vec![Shape::Square])], &settings()).expect("doc");
+        assert_eq!(doc["layers"].as_array().map(Vec::len), Some(6));
+        assert_eq!(doc["op"], 11);
+        assert_eq!(doc["fr"], 60.0);
+        let layer = &doc["layers"][0];
+        assert_eq!(layer["ty"], 4);
+        assert_eq!(layer["shapes"][0]["it"][0]["ty"], "rc");
+        assert_eq!(layer["ks"]["p"]["k"].as_array().map(Vec::len), Some(11));
+    }
+
+    #[test]
+    fn frames_match_the_raster_exporters() {
+        let bursts = [ConfettiOptions {
+            ticks: 120,
+            ..burst(vec![Shape::Square])
+        }];
+        let slow = ExportSettings {
+            fps: 2.0,
+            ..settings()
+        };
+        let doc = lottie_document(&bursts, &slow).expect("doc");
+        assert_eq!(doc["op"], slow.frames(&bursts).count());
+        assert!(doc["op"].as_u64().is_some_and(|op| op <= 3));
+    }
+
+    #[test]
+    fn shapes_and_colors_carry_over() {
+        let opts = ConfettiOptions {
+            colors: vec![Color::RED],
+            ..burst(vec![Shape::Star])
+        };
+        let doc = lottie_document(&[opts], &settings()).expect("doc");
+        let items = &doc["layers"][0]["shapes"][0]["it"];
+        assert_eq!(items[0]["ty"], "sr");
+        assert_eq!(items[1]["c"]["k"], json!([1.0, 0.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn path_shapes_become_bezier_paths() {
+        let path = Shape::path("M0 0 H2 V2 H0 Z M3 0 H4 V1 H3 Z").expect("path");
+        let doc = lottie_document(&[burst(vec![path])], &settings()).expect("doc");
+        let items = &doc["layers"][0]["shapes"][0]["it"];
+        assert_eq!(items[0]["ty"], "sh");
+        assert_eq!(items[1]["ty"], "sh");
+        assert_eq!(items[0]["ks"]["k"]["v"][0], json!([-10.0, -5.0]));
+        assert_eq!(items[2]["ty"], "fl");
+    }
+
+    #[test]
+    fn built_in_polygons_tilt_like_squares() {
+        let doc = lottie_document(&[burst(vec![Shape::Heart])], &settings()).expect("doc");
+        let layer = &doc["layers"][0];
+        assert_eq!(layer["shapes"][0]["it"][0]["ty"], "sh");
+        let rotations: Vec<&Value> = layer["ks"]["r"]["k"]
+            .as_array()
+            .expect("keyframes")
+            .iter()
+            .map(|k| &k["s"])
+            .collect();
+        assert!(rotations.iter().any(|r| *r != rotations[0]));
+    }
+
+    #[test]
+    fn ribbon_outlines_are_keyframed() {
+        let doc = lottie_document(&[burst(vec![Shape::Ribbon])], &settings()).expect("doc");
+        let path = &doc["layers"][0]["shapes"][0]["it"][0];
+        assert_eq!(path["ks"]["a"], 1);
+        let keys = path["ks"]["k"].as_array().expect("keyframes");
+        assert_eq!(keys.len(), 11);
+        let vertices = keys[0]["s"][0]["v"].as_array().expect("vertices");
+        assert_eq!(vertices.len(), 2 * crate::sim::RIBBON_POINTS);
+    }
+
+    #[test]
+    fn gradient_colors_are_keyframed() {
+        let opts = ConfettiOptions {
+            gradient: Some(crate::types::Gradient::even([Color::WHITE, Color::RED])),
+            ..burst(vec![Shape::Circle])
+        };
+        let doc = lottie_document(&[opts], &settings()).expect("doc");
+        let fill = &doc["layers"][0]["shapes"][0]["it"][1];
+        let keys = fill["c"]["k"].as_array().expect("keyframes");
+        assert_eq!(fill["c"]["a"], 1);
+        assert_ne!(keys[0]["s"], keys[keys.len() - 1]["s"]);
+    }
+
+    #[test]
+    fn background_is_bottom_layer() {
+        let settings = ExportSettings {
+            background: Some(Color::WHITE),
+            ..settings()
+        };
+        let doc = lottie_document(&[burst(vec![Shape::Circle])], &settings).expect("doc");
+        let layers = doc["layers"].as_array().expect("layers");
+        assert_eq!(layers.len(), 7);
+        assert_eq!(layers[6]["sc"], "#ffffff");
+    }
+
+    #[test]
+    fn writes_valid_json() {
+        let mut out = Vec::new();
+        export_lottie(&mut out, &[burst(vec![Shape::Circle])], &settings()).expect("export");
+        let parsed: Value = serde_json::from_slice(&out).expect("json");
+        assert_eq!(parsed["v"], "5.7.4");
+    }
+}
@@ -0,0 +1,125 @@
+//! Offscreen export of bursts to image and animation formats.
+//!
+//! Every exporter runs the bursts to completion through a
+//! [`FrameSource`](crate::raster::FrameSource) and encodes the frames, so no
+//! browser or GPU is needed.
+
+#[cfg(feature = "gif")]
+mod gif;
+#[cfg(feature = "lottie")]
+mod lottie;
+#[cfg(feature = "gif")]
+mod palette;
+#[cfg(feature = "png")]
+mod png;
+
+#[cfg(feature = "gif")]
+pub use self::gif::export_gif;
+#[cfg(feature = "lottie")]
+pub use self::lottie::{export_lottie, lottie_document};
+#[cfg(feature = "png")]
+pub use self::png::{export_apng, export_png_sequence, write_png};
+
+use crate::raster::FrameSource;
+use crate::types::{Color, ConfettiOptions};
+use std::fmt;
+use std::io;
+
+/// Output size, timing, quality and background shared by all exporters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportSettings {
+    pub width: u32,
+    pub height: u32,
+    /// Output frames per second. Physics always runs at 60 Hz.
+    pub fps: f64,
+    /// Opaque background color, or `None` for a transparent background.
+    pub background: Option<Color>,
+    /// Render at this multiple of the output size and average down for
+    /// smoother edges. `1` disables supersampling.
+    pub supersample: u32,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            width: 480,
+            height: 360,
+            fps: 30.0,
+            background: None,
+            supersample: 1,
+        }
+    }
+}
+
+/// Errors that can occur while exporting a burst.
+#[derive(Debug)]
+pub enum ExportError {
+    /// Width or height is zero or too large for the target format.
+    InvalidSize { width: u32, height: u32 },
+    /// The frame rate is not a positive, finite number.
+    InvalidFps(f64),
+    /// Writing the output failed.
+    Io(io::Error),
+    /// The encoder rejected the data.
+    Encode(String),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSize { width, height } => {
+                write!(f, "invalid export size {width}x{height}")
+            }
+            Self::InvalidFps(fps) => write!(f, "invalid export frame rate {fps}"),
+            Self::Io(err) => write!(f, "export I/O error: {err}"),
+            Self::Encode(msg) => write!(f, "export encoding error: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ExportError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl ExportSettings {
+    /// Check the settings against a format's maximum dimension.
+    fn validate(&self, max_side: u32) -> Result<(), ExportError> {
+        let scale = self.supersample.max(1);
+        let side_ok = |side: u32| side > 0 && side <= max_side && side.checked_mul(scale).is_some();
+        if !side_ok(self.width) || !side_ok(self.height) {
+            return Err(ExportError::InvalidSize {
+                width: self.width,
+                height: self.height,
+            });
+        }
+        if !(self.fps.is_finite() && self.fps > 0.0) {
+            return Err(ExportError::InvalidFps(self.fps));
+        }
+        Ok(())
+    }
+
+    /// A frame source with every burst spawned at time zero.
+    fn frames(&self, bursts: &[ConfettiOptions]) -> FrameSource {
+        let mut source = FrameSource::new(self.width, self.height)
+            .with_fps(self.fps)
+            .with_supersampling(self.supersample);
+        if let Some(color) = self.background {
+            source = source.with_background(color);
+        }
+        for opts in bursts {
+            source.spawn(opts);
+        }
+        source
+    }
+}
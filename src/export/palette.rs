@@ -0,0 +1,155 @@
+//! Indexed palettes built from a burst's own colors.
+
+use crate::color::Gradient;
+use crate::types::{Color, ConfettiOptions};
+use std::collections::HashMap;
+
+/// Most entries an indexed image format can hold.
+const MAX_ENTRIES: usize = 256;
+
+/// Upper bound on fade steps generated per particle color.
+const MAX_FADE_STEPS: usize = 16;
+
+/// Palette for 8-bit indexed output.
+///
+/// Over a background, each burst color is expanded into fade steps blended
+/// with that background, so particles fade out smoothly. Without one, entry
+/// 0 is transparent and every particle pixel maps to its nearest solid color.
+pub struct Palette {
+    entries: Vec<[u8; 3]>,
+    transparent: Option<u8>,
+    cache: HashMap<[u8; 4], u8>,
+}
+
+impl Palette {
+    #[must_use]
+    pub fn for_bursts(bursts: &[ConfettiOptions], background: Option<Color>) -> Self {
+        let mut colors: Vec<Color> = Vec::new();
+        let gradient_colors = bursts
+            .iter()
+            .flat_map(|b| b.gradient.iter().flat_map(Gradient::stops))
+            .map(|s| &s.color);
+        for color in bursts.iter().flat_map(|b| &b.colors).chain(gradient_colors) {
+            if !colors.contains(color) {
+                colors.push(*color);
+            }
+        }
+        colors.truncate(MAX_ENTRIES - 1);
+
+        let base = background.unwrap_or(Color::new(0, 0, 0));
+        let mut entries = vec![[base.r, base.g, base.b]];
+        let steps = if background.is_some() {
+            ((MAX_ENTRIES - 1) / colors.len().max(1)).min(MAX_FADE_STEPS)
+        } else {
+            1
+        };
+        for color in &colors {
+            entries.extend((1..=steps).map(|k| mix(*color, base, k, steps)));
+        }
+
+        Self {
+            entries,
+            transparent: background.is_none().then_some(0),
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Index of the transparent entry, if the palette has one.
+    #[must_use]
+    pub fn transparent(&self) -> Option<u8> {
+        self.transparent
+    }
+
+    /// Flat `RGBRGB...` bytes, as indexed encoders expect.
+    #[must_use]
+    pub fn rgb_bytes(&self) -> Vec<u8> {
+        self.entries.iter().flatten().copied().collect()
+    }
+
+    /// Map an RGBA pixel to its palette index.
+    pub fn index_of(&mut self, px: [u8; 4]) -> u8 {
+        if self.transparent.is_some() && px[3] < 128 {
+            return 0;
+        }
+        if let Some(&index) = self.cache.get(&px) {
+            return index;
+        }
+        let index = self.nearest([px[0], px[1], px[2]]);
+        self.cache.insert(px, index);
+        index
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn nearest(&self, rgb: [u8; 3]) -> u8 {
+        let skip = usize::from(self.transparent.is_some());
+        let distance = |e: &[u8; 3]| -> i32 {
+            (0..3)
+                .map(|c| (i32::from(e[c]) - i32::from(rgb[c])).pow(2))
+                .sum()
+        };
+        self.entries
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .min_by_key(|(_, e)| distance(e))
+            .map_or(0, |(i, _)| i as u8)
+    }
+}
+
+/// `color` blended over `base` at opacity `k / steps`.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn mix(color: Color, base: Color, k: usize, steps: usize) -> [u8; 3] {
+    let t = k as f64 / steps as f64;
+    let channel = |c: u8, b: u8| (f64::from(b) + (f64::from(c) - f64::from(b)) * t).round() as u8;
+    [
+        channel(color.r, base.r),
+        channel(color.g, base.g),
+        channel(color.b, base.b),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn burst(colors: Vec<Color>) -> ConfettiOptions {
+        ConfettiOptions {
+            colors,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn transparent_palette_holds_only_burst_colors() {
+        let mut palette = Palette::for_bursts(&[burst(vec![Color::RED, Color::BLUE])], None);
+        assert_eq!(palette.transparent(), Some(0));
+        assert_eq!(palette.rgb_bytes().len(), 9);
+        assert_eq!(palette.index_of([0, 0, 0, 0]), 0);
+        assert_eq!(palette.index_of([250, 10, 10, 255]), 1);
+        assert_eq!(palette.index_of([10, 10, 240, 200]), 2);
+    }
+
+    #[test]
+    fn background_palette_contains_fade_steps() {
+        let bursts = [
+            burst(vec![Color::RED]),
+            burst(vec![Color::RED, Color::GREEN]),
+        ];
+        let mut palette = Palette::for_bursts(&bursts, Some(Color::WHITE));
+        assert_eq!(palette.transparent(), None);
+        assert_eq!(palette.rgb_bytes().len(), 3 * (1 + 2 * MAX_FADE_STEPS));
+        assert_eq!(palette.index_of([255, 255, 255, 255]), 0);
+        assert_eq!(palette.index_of([255, 0, 0, 255]), 16);
+    }
+
+    #[test]
+    fn many_colors_fit_the_palette() {
+        let colors = (0..=255).map(|v| Color::new(v, 0, 0)).collect();
+        let palette = Palette::for_bursts(&[burst(colors)], Some(Color::WHITE));
+        assert!(palette.rgb_bytes().len() <= 3 * MAX_ENTRIES);
+    }
+}
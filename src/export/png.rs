@@ -0,0 +1,151 @@
+//! PNG sequence and APNG export.
+
+use super::{ExportError, ExportSettings};
+use crate::raster::Frame;
+use crate::types::ConfettiOptions;
+use png::{BitDepth, BlendOp, ColorType, DisposeOp, Encoder, EncodingError};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Largest side PNG allows.
+const MAX_SIDE: u32 = i32::MAX as u32;
+
+/// Encode a single frame as an RGBA PNG.
+///
+/// # Errors
+///
+/// Returns [`ExportError`] if the output cannot be written.
+pub fn write_png<W: Write>(writer: W, frame: &Frame) -> Result<(), ExportError> {
+    let mut encoder = Encoder::new(writer, frame.width(), frame.height());
+    encoder.set_color(ColorType::Rgba);
+    encoder.set_depth(BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(frame.pixels())?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Run `bursts` to completion offscreen and write every frame to `dir` as
+/// `frame_00000.png`, `frame_00001.png`, ... with full alpha.
+///
+/// The directory is created if needed. Returns the written paths in order.
+///
+/// # Errors
+///
+/// Returns [`ExportError`] if the settings are invalid or a file cannot be
+/// written.
+pub fn export_png_sequence(
+    dir: impl AsRef<Path>,
+    bursts: &[ConfettiOptions],
+    settings: &ExportSettings,
+) -> Result<Vec<PathBuf>, ExportError> {
+    settings.validate(MAX_SIDE)?;
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+    let mut paths = Vec::new();
+    for (i, frame) in settings.frames(bursts).enumerate() {
+        let path = dir.join(format!("frame_{i:05}.png"));
+        write_png(BufWriter::new(File::create(&path)?), &frame)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Run `bursts` to completion offscreen and encode them as a looping APNG.
+///
+/// # Errors
+///
+/// Returns [`ExportError`] if the settings are invalid, the bursts produce no
+/// frames, or the output cannot be written.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn export_apng<W: Write>(
+    writer: W,
+    bursts: &[ConfettiOptions],
+    settings: &ExportSettings,
+) -> Result<(), ExportError> {
+    settings.validate(MAX_SIDE)?;
+    let mut encoder = Encoder::new(writer, settings.width, settings.height);
+    encoder.set_color(ColorType::Rgba);
+    encoder.set_depth(BitDepth::Eight);
+    encoder.set_animated(frame_count(bursts, settings), 0)?;
+    // Delay is 100 / (fps * 100) seconds, which keeps fractional rates exact.
+    let den = (settings.fps * 100.0)
+        .round()
+        .clamp(1.0, f64::from(u16::MAX)) as u16;
+    encoder.set_frame_delay(100, den)?;
+    encoder.set_blend_op(BlendOp::Source)?;
+    encoder.set_dispose_op(DisposeOp::Background)?;
+
+    let mut writer = encoder.write_header()?;
+    for frame in settings.frames(bursts) {
+        writer.write_image_data(frame.pixels())?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Number of frames the bursts will produce. Lifetime depends only on
+/// `ticks` and the frame rate, not on randomness, so a dry run is exact.
+fn frame_count(bursts: &[ConfettiOptions], settings: &ExportSettings) -> u32 {
+    let mut source = settings.frames(bursts);
+    let mut count = 0;
+    while source.advance() {
+        count += 1;
+    }
+    count
+}
+
+impl From<EncodingError> for ExportError {
+    fn from(err: EncodingError) -> Self {
+        match err {
+            EncodingError::IoError(err) => Self::Io(err),
+            other => Self::Encode(other.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn burst() -> ConfettiOptions {
+        ConfettiOptions {
+            seed: Some(11),
+            ticks: 20,
+            ..Default::default()
+        }
+    }
+
+    fn settings() -> ExportSettings {
+        ExportSettings {
+            width: 32,
+            height: 24,
+            supersample: 2,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn sequence_writes_numbered_files() {
+        let dir = std::env::temp_dir().join(format!("glitterbomb-seq-{}", std::process::id()));
+        let paths = export_png_sequence(&dir, &[burst()], &settings()).expect("export");
+        assert_eq!(paths.len(), 9);
+        assert!(paths[0].ends_with("frame_00000.png"));
+        let decoder = png::Decoder::new(File::open(&paths[3]).expect("open"));
+        let info = decoder.read_info().expect("decode").info().clone();
+        assert_eq!((info.width, info.height), (32, 24));
+        assert_eq!(info.color_type, ColorType::Rgba);
+        fs::remove_dir_all(dir).expect("cleanup");
+    }
+
+    #[test]
+    fn apng_declares_every_frame() {
+        let mut out = Vec::new();
+        export_apng(&mut out, &[burst()], &settings()).expect("export");
+        let decoder = png::Decoder::new(out.as_slice());
+        let reader = decoder.read_info().expect("decode");
+        let control = reader.info().animation_control.expect("animated");
+        assert_eq!(control.num_frames, 9);
+    }
+}
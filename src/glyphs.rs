@@ -0,0 +1,144 @@
+//! Text and emoji rasterized once into offscreen canvases for reuse, with
+//! plain text tinted once per color.
+
+use crate::animation;
+use crate::types::Color;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use web_sys::HtmlCanvasElement;
+
+/// Font size glyphs are rasterized at, in CSS pixels. Large enough to stay
+/// sharp when particles are scaled up.
+const GLYPH_PX: f64 = 64.0;
+
+/// Transparent margin around each glyph so antialiased edges are kept.
+const PADDING: f64 = 2.0;
+
+/// Entries kept before the cache is flushed, bounding memory when many
+/// distinct texts are used.
+const MAX_GLYPHS: usize = 512;
+
+/// Tinted copies kept per glyph before they are flushed, bounding memory
+/// when colors change continuously, as with gradients.
+const MAX_TINTS: usize = 64;
+
+/// A piece of text rasterized once, in white for plain text so it can be
+/// tinted to any particle color, or in its own colors for emoji.
+#[derive(Clone)]
+pub struct Glyph {
+    canvas: HtmlCanvasElement,
+    /// Whether the glyph is a white mask to tint, rather than colored.
+    mask: bool,
+    /// The mask tinted in each color drawn so far, by RGB.
+    tints: Rc<RefCell<HashMap<[u8; 3], HtmlCanvasElement>>>,
+    pub width: f64,
+    pub height: f64,
+}
+
+thread_local! {
+    static GLYPHS: RefCell<HashMap<(String, String), Glyph>> = RefCell::new(HashMap::new());
+}
+
+impl Glyph {
+    /// This glyph in `color`: the mask tinted once per color and kept, or
+    /// the colored glyph itself.
+    pub fn paint(&self, color: Color) -> Option<HtmlCanvasElement> {
+        if !self.mask {
+            return Some(self.canvas.clone());
+        }
+        let rgb = [color.r, color.g, color.b];
+        let mut tints = self.tints.borrow_mut();
+        if let Some(tinted) = tints.get(&rgb) {
+            return Some(tinted.clone());
+        }
+        let tinted = self.tint(rgb)?;
+        if tints.len() >= MAX_TINTS {
+            tints.clear();
+        }
+        tints.insert(rgb, tinted.clone());
+        Some(tinted)
+    }
+
+    /// A new canvas holding the mask filled with `rgb`.
+    fn tint(&self, [r, g, b]: [u8; 3]) -> Option<HtmlCanvasElement> {
+        let (canvas, ctx) = animation::scratch_canvas()?;
+        canvas.set_width(self.canvas.width());
+        canvas.set_height(self.canvas.height());
+        ctx.draw_image_with_html_canvas_element(&self.canvas, 0.0, 0.0)
+            .ok()?;
+        let _ = ctx.set_global_composite_operation("source-in");
+        ctx.set_fill_style_str(&format!("rgb({r}, {g}, {b})"));
+        ctx.fill_rect(0.0, 0.0, self.width, self.height);
+        Some(canvas)
+    }
+}
+
+/// The glyph for `text` in `font`, rasterizing it on first use. `None` if
+/// no canvas is available.
+pub fn glyph(text: &str, font: &str) -> Option<Glyph> {
+    let key = (text.to_string(), font.to_string());
+    GLYPHS.with(|glyphs| {
+        let mut glyphs = glyphs.borrow_mut();
+        if let Some(glyph) = glyphs.get(&key) {
+            return Some(glyph.clone());
+        }
+        let glyph = rasterize(text, font)?;
+        if glyphs.len() >= MAX_GLYPHS {
+            glyphs.clear();
+        }
+        glyphs.insert(key, glyph.clone());
+        Some(glyph)
+    })
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn rasterize(text: &str, font: &str) -> Option<Glyph> {
+    let (canvas, ctx) = animation::scratch_canvas()?;
+    let font = format!("{GLYPH_PX}px {font}");
+    ctx.set_font(&font);
+    let metrics = ctx.measure_text(text).ok()?;
+    let ascent = metrics.actual_bounding_box_ascent();
+    let descent = metrics.actual_bounding_box_descent();
+    let width = metrics.width().ceil() + 2.0 * PADDING;
+    let height = (ascent + descent).ceil() + 2.0 * PADDING;
+    if width <= 2.0 * PADDING || height <= 2.0 * PADDING {
+        return None;
+    }
+
+    // Resizing resets the context state, so set the font again afterwards.
+    canvas.set_width(width as u32);
+    canvas.set_height(height as u32);
+    ctx.set_font(&font);
+    ctx.set_fill_style_str("#fff");
+    ctx.fill_text(text, PADDING, PADDING + ascent).ok()?;
+    let pixels = ctx.get_image_data(0.0, 0.0, width, height).ok()?.data();
+    Some(Glyph {
+        canvas,
+        mask: is_gray(&pixels),
+        tints: Rc::default(),
+        width,
+        height,
+    })
+}
+
+/// Whether every pixel of an RGBA buffer is a shade of gray, as text filled
+/// in white is and color emoji are not.
+fn is_gray(pixels: &[u8]) -> bool {
+    pixels
+        .chunks_exact(4)
+        .all(|px| px[0] == px[1] && px[1] == px[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_gray_pixels_make_a_mask() {
+        let white_text = [255, 255, 255, 255, 128, 128, 128, 90, 0, 0, 0, 0];
+        let emoji = [255, 255, 255, 255, 250, 200, 40, 255];
+        assert!(is_gray(&white_text));
+        assert!(!is_gray(&emoji));
+    }
+}
@@ -0,0 +1,218 @@
+//! Handles that resolve when a burst's particles are gone.
+//!
+//! A render loop pairs every spawned burst with a [`Completion`] in a
+//! [`BurstTracker`], and calls [`BurstTracker::sync`] after each frame. The
+//! caller holds the matching [`BurstHandle`], which can be awaited or
+//! cancelled.
+
+use crate::sim::{BurstId, Simulation};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+#[derive(Debug, Default)]
+struct Shared {
+    finished: bool,
+    cancelled: bool,
+    wakers: Vec<Waker>,
+}
+
+/// One or more bursts in flight.
+///
+/// Resolves as a [`Future`] (or, from JavaScript, through `then`/`promise`)
+/// once every particle of the bursts has expired, been cancelled or been
+/// cleared by `reset`. Clones share the same bursts.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "web", wasm_bindgen::prelude::wasm_bindgen)]
+pub struct BurstHandle {
+    shared: Arc<Mutex<Shared>>,
+}
+
+/// Resolving side of a [`BurstHandle`]. Dropping it finishes the handle, so
+/// a burst that never reaches a render loop cannot leave a caller waiting.
+#[derive(Debug)]
+pub struct Completion {
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl BurstHandle {
+    /// A pending handle and the completion that resolves it.
+    #[must_use]
+    pub fn new() -> (Self, Completion) {
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        let completion = Completion {
+            shared: Arc::clone(&shared),
+        };
+        (Self { shared }, completion)
+    }
+
+    /// A handle that has already resolved, for bursts that never started.
+    #[must_use]
+    pub fn finished() -> Self {
+        let (handle, completion) = Self::new();
+        drop(completion);
+        handle
+    }
+
+    /// Whether the bursts are over.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        lock(&self.shared).finished
+    }
+
+    /// Remove the bursts' particles on the next frame and resolve now,
+    /// leaving other bursts running.
+    pub fn cancel(&self) {
+        lock(&self.shared).cancelled = true;
+        finish(&self.shared);
+    }
+}
+
+impl Future for BurstHandle {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut shared = lock(&self.shared);
+        if shared.finished {
+            return Poll::Ready(());
+        }
+        if !shared.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            shared.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+impl Completion {
+    /// Whether the handle asked for its bursts to be removed.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        lock(&self.shared).cancelled
+    }
+}
+
+impl Drop for Completion {
+    fn drop(&mut self) {
+        finish(&self.shared);
+    }
+}
+
+/// Pairs bursts in a [`Simulation`] with the completions of their handles.
+#[derive(Debug, Default)]
+pub struct BurstTracker {
+    watched: Vec<(Vec<BurstId>, Completion)>,
+}
+
+impl BurstTracker {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolve `completion` once all of `bursts` are gone.
+    pub fn watch(&mut self, bursts: Vec<BurstId>, completion: Completion) {
+        self.watched.push((bursts, completion));
+    }
+
+    /// Spawn-side shorthand: track `bursts` and return their handle.
+    #[must_use]
+    pub fn handle(&mut self, bursts: Vec<BurstId>) -> BurstHandle {
+        let (handle, completion) = BurstHandle::new();
+        self.watch(bursts, completion);
+        handle
+    }
+
+    /// Remove cancelled bursts from `sim` and resolve handles whose bursts
+    /// have no particles left.
+    pub fn sync(&mut self, sim: &mut Simulation) {
+        self.watched.retain(|(bursts, completion)| {
+            if completion.is_cancelled() {
+                for &burst in bursts {
+                    sim.cancel(burst);
+                }
+                return false;
+            }
+            bursts.iter().any(|&b| sim.contains(b))
+        });
+    }
+
+    /// Number of handles still pending.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.watched.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.watched.is_empty()
+    }
+}
+
+fn lock(shared: &Mutex<Shared>) -> MutexGuard<'_, Shared> {
+    shared
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+fn finish(shared: &Mutex<Shared>) {
+    let wakers = {
+        let mut shared = lock(shared);
+        shared.finished = true;
+        std::mem::take(&mut shared.wakers)
+    };
+    wakers.into_iter().for_each(Waker::wake);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ConfettiOptions;
+    use futures::FutureExt;
+
+    fn opts() -> ConfettiOptions {
+        ConfettiOptions {
+            particle_count: 5,
+            ticks: 3,
+            seed: Some(1),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn resolves_when_particles_expire() {
+        let mut sim = Simulation::new(|| 0.5);
+        let mut tracker = BurstTracker::new();
+        let handle = tracker.handle(vec![sim.spawn(&opts(), 100.0, 100.0)]);
+        tracker.sync(&mut sim);
+        assert!(handle.clone().now_or_never().is_none());
+        while sim.step() {}
+        tracker.sync(&mut sim);
+        assert!(handle.is_finished());
+        assert_eq!(handle.now_or_never(), Some(()));
+    }
+
+    #[test]
+    fn cancel_removes_only_its_burst() {
+        let mut sim = Simulation::new(|| 0.5);
+        let mut tracker = BurstTracker::new();
+        let first = tracker.handle(vec![sim.spawn(&opts(), 100.0, 100.0)]);
+        let second = tracker.handle(vec![sim.spawn(&opts(), 100.0, 100.0)]);
+        first.cancel();
+        assert!(first.is_finished());
+        tracker.sync(&mut sim);
+        assert_eq!(sim.particles().len(), 5);
+        assert!(!second.is_finished());
+        assert_eq!(tracker.len(), 1);
+    }
+
+    #[test]
+    fn dropping_the_tracker_resolves_handles() {
+        let mut sim = Simulation::new(|| 0.5);
+        let mut tracker = BurstTracker::new();
+        let handle = tracker.handle(vec![sim.spawn(&opts(), 100.0, 100.0)]);
+        drop(tracker);
+        assert!(handle.is_finished());
+        assert!(BurstHandle::finished().is_finished());
+    }
+}
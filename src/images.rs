@@ -0,0 +1,102 @@
+//! Browser image sources behind [`ImageShape`]s.
+//!
+//! JavaScript objects cannot live inside a `Send` shape, so each browser
+//! image is kept here under its shape's id and dropped with the last clone.
+
+use crate::animation;
+use crate::types::{ImageError, ImageShape};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use wasm_bindgen::Clamped;
+use web_sys::{
+    CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement, ImageBitmap, ImageData,
+};
+
+/// Something `drawImage` accepts.
+#[derive(Clone)]
+enum Source {
+    Image(HtmlImageElement),
+    Bitmap(ImageBitmap),
+    /// RGBA pixels uploaded to a canvas on first draw.
+    Canvas(HtmlCanvasElement),
+}
+
+thread_local! {
+    static SOURCES: RefCell<HashMap<u64, Source>> = RefCell::new(HashMap::new());
+}
+
+impl ImageShape {
+    /// Sprite drawn from a loaded `<img>` element.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImageError::NotLoaded`] if the image has no size yet.
+    pub fn from_html_image(image: &HtmlImageElement) -> Result<Self, ImageError> {
+        if !image.complete() || image.natural_width() == 0 {
+            return Err(ImageError::NotLoaded);
+        }
+        let shape = Self::new(image.natural_width(), image.natural_height(), None)?;
+        register(shape.id(), Source::Image(image.clone()));
+        Ok(shape)
+    }
+
+    /// Sprite drawn from an `ImageBitmap`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImageError::InvalidSize`] if the bitmap has been closed.
+    pub fn from_image_bitmap(bitmap: &ImageBitmap) -> Result<Self, ImageError> {
+        let shape = Self::new(bitmap.width(), bitmap.height(), None)?;
+        register(shape.id(), Source::Bitmap(bitmap.clone()));
+        Ok(shape)
+    }
+}
+
+fn register(id: u64, source: Source) {
+    SOURCES.with(|sources| sources.borrow_mut().insert(id, source));
+}
+
+/// Drop the source of a freed image.
+pub fn forget(id: u64) {
+    let _ = SOURCES.try_with(|sources| sources.borrow_mut().remove(&id));
+}
+
+/// Draw `image` centred on the origin, spanning the unit box along its
+/// longer side.
+pub fn draw(ctx: &CanvasRenderingContext2d, image: &ImageShape) {
+    let Some(source) = source(image) else {
+        return;
+    };
+    let (hw, hh) = image.half_extents();
+    let (x, y, w, h) = (-hw, -hh, 2.0 * hw, 2.0 * hh);
+    let _ = match &source {
+        Source::Image(img) => ctx.draw_image_with_html_image_element_and_dw_and_dh(img, x, y, w, h),
+        Source::Bitmap(bitmap) => {
+            ctx.draw_image_with_image_bitmap_and_dw_and_dh(bitmap, x, y, w, h)
+        }
+        Source::Canvas(canvas) => {
+            ctx.draw_image_with_html_canvas_element_and_dw_and_dh(canvas, x, y, w, h)
+        }
+    };
+}
+
+fn source(image: &ImageShape) -> Option<Source> {
+    if let Some(source) = SOURCES.with(|sources| sources.borrow().get(&image.id()).cloned()) {
+        return Some(source);
+    }
+    let source = Source::Canvas(upload(image)?);
+    register(image.id(), source.clone());
+    Some(source)
+}
+
+/// Copy RGBA pixels into a canvas of the image's size.
+fn upload(image: &ImageShape) -> Option<HtmlCanvasElement> {
+    let pixels = image.pixels()?;
+    let (width, height) = image.size();
+    let (canvas, ctx) = animation::scratch_canvas()?;
+    canvas.set_width(width);
+    canvas.set_height(height);
+    let data = ImageData::new_with_u8_clamped_array_and_sh(Clamped(pixels), width, height).ok()?;
+    ctx.put_image_data(&data, 0.0, 0.0).ok()?;
+    Some(canvas)
+}
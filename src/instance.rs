@@ -0,0 +1,329 @@
+//! Independent confetti instances, each with its own canvas and loop.
+
+use crate::animation::{self, request_animation_frame};
+use crate::canvas;
+use crate::compositing::{Compositing, Layer};
+use crate::error::GlitterbombError;
+use crate::handle::{BurstHandle, BurstTracker};
+use crate::presets::Preset;
+use crate::sim::Simulation;
+use crate::types::ConfettiOptions;
+use crate::viewport::{self, ResizeWatch, Viewport};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+/// Behaviour shared by every burst of a [`Confetti`] instance.
+#[derive(Clone, Debug, Default)]
+pub struct ConfettiSettings {
+    /// Size a user canvas's drawing buffer to its on-page size, scaled by
+    /// `devicePixelRatio`, before each burst and whenever it changes size
+    /// while animating. The fullscreen overlay always follows the window.
+    pub resize: bool,
+    /// Stacking order of the overlay canvas. `None` takes the first burst's
+    /// `z_index`.
+    pub z_index: Option<i32>,
+    /// Skip every burst when the user prefers reduced motion, whatever the
+    /// burst's own setting.
+    pub disable_for_reduced_motion: bool,
+    /// How confetti shares a user canvas with what is already drawn there.
+    pub compositing: Compositing,
+}
+
+/// A confetti cannon with its own canvas, animation loop, particles and
+/// reset, so several can run on one page without interfering.
+///
+/// Mirrors `confetti.create()` from canvas-confetti. Clones share the same
+/// instance.
+#[derive(Clone)]
+#[wasm_bindgen]
+pub struct Confetti {
+    state: Rc<RefCell<State>>,
+}
+
+struct State {
+    /// User canvas to draw on, or `None` for an owned fullscreen overlay.
+    target: Option<HtmlCanvasElement>,
+    settings: ConfettiSettings,
+    surface: Option<(HtmlCanvasElement, CanvasRenderingContext2d)>,
+    viewport: Viewport,
+    /// Size changes of the surface, watched while animating.
+    watch: Option<ResizeWatch>,
+    layer: Layer,
+    sim: Simulation,
+    tracker: BurstTracker,
+    is_animating: bool,
+}
+
+impl Confetti {
+    /// Instance drawing on `target`, or on its own fullscreen overlay when
+    /// `target` is `None`. Nothing touches the page until the first burst.
+    #[must_use]
+    pub fn create(target: Option<&HtmlCanvasElement>, settings: ConfettiSettings) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(State {
+                target: target.cloned(),
+                settings,
+                surface: None,
+                viewport: Viewport {
+                    width: 0.0,
+                    height: 0.0,
+                    scale: 1.0,
+                },
+                watch: None,
+                layer: Layer::default(),
+                sim: Simulation::new(animation::random),
+                tracker: BurstTracker::new(),
+                is_animating: false,
+            })),
+        }
+    }
+
+    /// Fire one burst. Failures are logged to the console and yield an
+    /// already finished handle; use [`try_fire`](Self::try_fire) to handle
+    /// them yourself.
+    pub fn fire(&self, opts: &ConfettiOptions) -> BurstHandle {
+        self.try_fire(opts).unwrap_or_else(warned)
+    }
+
+    /// Fire one burst, reporting why it could not start.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GlitterbombError`] if the canvas has no 2D context, or the
+    /// overlay cannot be created or animated.
+    pub fn try_fire(&self, opts: &ConfettiOptions) -> Result<BurstHandle, GlitterbombError> {
+        self.launch(std::slice::from_ref(opts))
+    }
+
+    /// Fire every burst of a preset, with one handle for all of them.
+    pub fn fire_preset(&self, preset: &Preset) -> BurstHandle {
+        self.try_fire_preset(preset).unwrap_or_else(warned)
+    }
+
+    /// Fire every burst of a preset, reporting why it could not start.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GlitterbombError`] as [`try_fire`](Self::try_fire) does;
+    /// no burst is fired in that case.
+    pub fn try_fire_preset(&self, preset: &Preset) -> Result<BurstHandle, GlitterbombError> {
+        self.launch(preset.bursts())
+    }
+
+    /// Remove this instance's particles, resolving its handles. An owned
+    /// overlay is detached; a user canvas is erased as its
+    /// [`Compositing`] mode prescribes.
+    pub fn reset(&self) {
+        if let Ok(mut state) = self.state.try_borrow_mut() {
+            state.reset();
+        }
+    }
+
+    /// Whether this instance's loop is running.
+    #[must_use]
+    pub fn is_animating(&self) -> bool {
+        self.state.borrow().is_animating
+    }
+
+    fn launch(&self, bursts: &[ConfettiOptions]) -> Result<BurstHandle, GlitterbombError> {
+        let (handle, start) = {
+            let Ok(mut state) = self.state.try_borrow_mut() else {
+                return Ok(BurstHandle::finished());
+            };
+            let skip_all = state.settings.disable_for_reduced_motion;
+            let bursts: Vec<&ConfettiOptions> = bursts
+                .iter()
+                .filter(|o| {
+                    !((skip_all || o.disable_for_reduced_motion)
+                        && animation::prefers_reduced_motion())
+                })
+                .collect();
+            let Some(first) = bursts.first() else {
+                return Ok(BurstHandle::finished());
+            };
+            let (width, height) = state.prepare(first.z_index)?;
+            let ids = bursts
+                .iter()
+                .map(|opts| state.sim.spawn(opts, width, height))
+                .collect();
+            let handle = state.tracker.handle(ids);
+            // The watch is dropped when the loop finishes and on reset,
+            // which leaves the loop running.
+            if state.watch.is_none() {
+                state.watch = state.watch_size();
+            }
+            let start = !state.is_animating;
+            state.is_animating = true;
+            (handle, start)
+        };
+        if start {
+            self.start().inspect_err(|_| {
+                self.reset();
+                self.state.borrow_mut().is_animating = false;
+            })?;
+        }
+        Ok(handle)
+    }
+
+    /// Drive the instance every animation frame until its particles are gone.
+    fn start(&self) -> Result<(), GlitterbombError> {
+        let f = Rc::new(RefCell::new(None::<Closure<dyn FnMut(f64)>>));
+        let g = f.clone();
+        let state = Rc::clone(&self.state);
+
+        *g.borrow_mut() = Some(Closure::new(move |timestamp: f64| {
+            let should_continue = match state.try_borrow_mut() {
+                Ok(mut state) => state.frame(timestamp),
+                Err(_) => true,
+            };
+
+            let next = should_continue
+                .then(|| request_animation_frame(f.borrow().as_ref().expect("closure exists")));
+            match next {
+                Some(Ok(_)) => {}
+                Some(Err(err)) => {
+                    animation::warn(&err);
+                    state.borrow_mut().finish();
+                }
+                None => state.borrow_mut().finish(),
+            }
+        }));
+
+        let first = request_animation_frame(g.borrow().as_ref().expect("closure exists"));
+        first.map(|_| ())
+    }
+}
+
+impl State {
+    /// Make sure there is a surface to draw on, sized for the next burst,
+    /// and return that size.
+    fn prepare(&mut self, z_index: i32) -> Result<(f64, f64), GlitterbombError> {
+        if self.surface.is_none() {
+            self.surface = Some(match &self.target {
+                Some(canvas) => (canvas.clone(), animation::get_context(canvas)?),
+                None => animation::create_canvas(self.settings.z_index.unwrap_or(z_index))?,
+            });
+        }
+        self.fit();
+        Ok((self.viewport.width, self.viewport.height))
+    }
+
+    /// Size the surface's drawing buffer for the current display, unless it
+    /// is a user canvas the caller sizes.
+    fn fit(&mut self) {
+        let Some((canvas, _)) = &self.surface else {
+            return;
+        };
+        let viewport = match &self.target {
+            None => viewport::fill_window(canvas),
+            Some(_) if self.settings.resize => viewport::fit(canvas).unwrap_or(self.viewport),
+            Some(_) => Viewport::of(canvas),
+        };
+        if viewport != self.viewport {
+            // Resizing wiped the drawing buffer, and any snapshot with it.
+            self.layer = Layer::default();
+            self.viewport = viewport;
+        }
+    }
+
+    /// Watch whatever [`fit`](Self::fit) follows for size changes.
+    fn watch_size(&self) -> Option<ResizeWatch> {
+        match &self.target {
+            None => ResizeWatch::window(),
+            Some(canvas) if self.settings.resize => ResizeWatch::element(canvas),
+            Some(_) => None,
+        }
+    }
+
+    /// Draw one frame. Returns `false` once there is nothing left to draw.
+    fn frame(&mut self, timestamp: f64) -> bool {
+        if self.watch.as_ref().is_some_and(ResizeWatch::take) {
+            self.fit();
+        }
+        let Some((canvas, ctx)) = &self.surface else {
+            return false;
+        };
+        self.layer.erase(&self.settings.compositing, canvas, ctx);
+
+        self.sim.advance_to(timestamp / 1000.0);
+        self.tracker.sync(&mut self.sim);
+        self.viewport.enter(ctx);
+        let drawn = canvas::render_frame(&self.sim, ctx);
+        Viewport::leave(ctx);
+        self.layer.track(&drawn, self.viewport.scale);
+        !self.sim.is_empty()
+    }
+
+    /// Stop animating, detaching an owned overlay.
+    fn finish(&mut self) {
+        self.is_animating = false;
+        self.watch = None;
+        self.layer = Layer::default();
+        if self.target.is_none() {
+            if let Some((canvas, _)) = self.surface.take() {
+                canvas.remove();
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.sim.clear();
+        self.tracker = BurstTracker::new();
+        self.watch = None;
+        match (&self.target, self.surface.take()) {
+            (None, Some((canvas, _))) => canvas.remove(),
+            (Some(_), Some((canvas, ctx))) => {
+                self.layer
+                    .restore(&self.settings.compositing, &canvas, &ctx);
+            }
+            (_, None) => {}
+        }
+    }
+}
+
+/// Report `err` on the console and stand in an already finished handle.
+// Takes the error by value to serve directly as an `unwrap_or_else` callback.
+#[allow(clippy::needless_pass_by_value)]
+pub(crate) fn warned(err: GlitterbombError) -> BurstHandle {
+    animation::warn(&err);
+    BurstHandle::finished()
+}
+
+#[wasm_bindgen]
+impl Confetti {
+    /// `Confetti.create(canvas?, resize?)`: an instance on `canvas`, or on
+    /// its own overlay when omitted.
+    #[wasm_bindgen(js_name = create)]
+    #[must_use]
+    // wasm_bindgen cannot pass an optional element by reference.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn create_js(canvas: Option<HtmlCanvasElement>, resize: Option<bool>) -> Self {
+        let settings = ConfettiSettings {
+            resize: resize.unwrap_or(false),
+            ..ConfettiSettings::default()
+        };
+        Self::create(canvas.as_ref(), settings)
+    }
+
+    /// Fire the default burst.
+    #[wasm_bindgen(js_name = fire)]
+    #[must_use]
+    pub fn fire_js(&self) -> BurstHandle {
+        self.fire(&ConfettiOptions::default())
+    }
+
+    /// Fire the preset registered or built in under `name`, or return
+    /// `undefined` if there is none.
+    #[wasm_bindgen(js_name = firePreset)]
+    #[must_use]
+    pub fn fire_preset_js(&self, name: &str) -> Option<BurstHandle> {
+        crate::presets::preset(name).map(|p| self.fire_preset(&p))
+    }
+
+    #[wasm_bindgen(js_name = reset)]
+    pub fn reset_js(&self) {
+        self.reset();
+    }
+}
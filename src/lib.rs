@@ -4,28 +4,67 @@
 
 #![allow(non_snake_case)]
 
-// Shared types (needed by both web and desktop)
+// Shared types and simulation core (needed by both web and desktop)
+pub mod builder;
+pub mod color;
+#[cfg(feature = "serde")]
+pub mod config;
+pub mod handle;
+pub mod presets;
+pub mod shape;
+pub mod sim;
+pub mod system;
 pub mod types;
 
+// Headless rendering and offscreen export
+#[cfg(any(feature = "gif", feature = "png", feature = "lottie"))]
+pub mod export;
+pub mod raster;
+
 // Web-only modules
 #[cfg(feature = "web")]
 mod animation;
+#[cfg(feature = "web")]
+mod canvas;
+#[cfg(feature = "web")]
+pub mod compositing;
+#[cfg(feature = "web")]
+pub mod error;
+#[cfg(feature = "web")]
+mod glyphs;
+#[cfg(feature = "web")]
+mod images;
+#[cfg(feature = "web")]
+pub mod instance;
+#[cfg(feature = "web")]
+mod viewport;
 
 // Platform-specific implementations
 #[cfg(feature = "desktop")]
 pub mod desktop;
 #[cfg(feature = "web")]
 pub mod web;
-
-// Particle implementations
-#[cfg(feature = "web")]
-mod particle {
-    pub mod wasm;
-    pub use wasm::Particle;
-}
+#[cfg(feature = "worker")]
+pub mod worker;
 
 // Re-exports based on feature
+#[cfg(feature = "web")]
+pub use compositing::Compositing;
 #[cfg(all(feature = "desktop", not(feature = "web")))]
-pub use desktop::{cannon, celebration, confetti, confetti_on_canvas, fireworks, reset, snow};
+pub use desktop::{
+    cannon, celebration, confetti, confetti_on_canvas, fire, fire_preset, fireworks, reset, snow,
+};
+#[cfg(feature = "web")]
+pub use error::GlitterbombError;
+pub use handle::BurstHandle;
+#[cfg(feature = "web")]
+pub use instance::{Confetti, ConfettiSettings};
+pub use presets::{register_preset, Preset};
+pub use system::ConfettiSystem;
 #[cfg(feature = "web")]
-pub use web::{cannon, celebration, confetti, confetti_on_canvas, fireworks, reset, snow};
+pub use web::{
+    cannon, celebration, confetti, confetti_on_canvas, fire, fire_preset, fireworks, reset,
+    reset_canvas, snow, try_confetti, try_confetti_on_canvas, try_fire,
+};
+#[cfg(feature = "worker")]
+pub use worker::{run_worker, use_worker};
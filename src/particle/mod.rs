@@ -1,5 +0,0 @@
-//! Particle state and physics (WASM version)
-
-mod wasm;
-
-pub use wasm::Particle;
@@ -0,0 +1,194 @@
+//! Named confetti effects.
+//!
+//! A [`Preset`] is one or more bursts fired together. The built-in presets
+//! (`celebration`, `fireworks`, `snow`, `cannon`) are always available;
+//! presets registered with [`register_preset`] are stored alongside them and
+//! take precedence over a built-in of the same name.
+
+use crate::types::{Color, ConfettiOptions, Origin};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Names of the presets that ship with the crate.
+pub const BUILTIN_PRESETS: [&str; 4] = ["celebration", "fireworks", "snow", "cannon"];
+
+static REGISTRY: Mutex<BTreeMap<String, Preset>> = Mutex::new(BTreeMap::new());
+
+/// One or more bursts fired together.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Preset {
+    bursts: Vec<ConfettiOptions>,
+}
+
+impl Preset {
+    #[must_use]
+    pub fn new(bursts: Vec<ConfettiOptions>) -> Self {
+        Self { bursts }
+    }
+
+    #[must_use]
+    pub fn bursts(&self) -> &[ConfettiOptions] {
+        &self.bursts
+    }
+
+    /// Confetti from both sides of the screen.
+    #[must_use]
+    pub fn celebration() -> Self {
+        Self::new(vec![
+            ConfettiOptions {
+                particle_count: 50,
+                angle: 60.0,
+                spread: 55.0,
+                origin: Origin { x: 0.0, y: 0.6 },
+                ..Default::default()
+            },
+            ConfettiOptions {
+                particle_count: 50,
+                angle: 120.0,
+                spread: 55.0,
+                origin: Origin { x: 1.0, y: 0.6 },
+                ..Default::default()
+            },
+        ])
+    }
+
+    /// A burst in every direction from the centre.
+    #[must_use]
+    pub fn fireworks() -> Self {
+        Self::from(ConfettiOptions {
+            particle_count: 100,
+            spread: 360.0,
+            start_velocity: 30.0,
+            gravity: 0.5,
+            origin: Origin { x: 0.5, y: 0.5 },
+            ..Default::default()
+        })
+    }
+
+    /// Gentle snow-like falling confetti.
+    #[must_use]
+    pub fn snow() -> Self {
+        Self::from(ConfettiOptions {
+            particle_count: 50,
+            spread: 180.0,
+            start_velocity: 10.0,
+            gravity: 0.3,
+            ticks: 400,
+            origin: Origin { x: 0.5, y: 0.0 },
+            colors: vec![Color::WHITE, Color::from_hex("#e0e0e0")],
+            ..Default::default()
+        })
+    }
+
+    /// A blast from the bottom of the screen.
+    #[must_use]
+    pub fn cannon() -> Self {
+        Self::from(ConfettiOptions {
+            particle_count: 150,
+            spread: 60.0,
+            start_velocity: 55.0,
+            origin: Origin { x: 0.5, y: 1.0 },
+            ..Default::default()
+        })
+    }
+
+    /// The built-in preset called `name`, ignoring registered overrides.
+    #[must_use]
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "celebration" => Some(Self::celebration()),
+            "fireworks" => Some(Self::fireworks()),
+            "snow" => Some(Self::snow()),
+            "cannon" => Some(Self::cannon()),
+            _ => None,
+        }
+    }
+}
+
+impl From<ConfettiOptions> for Preset {
+    fn from(opts: ConfettiOptions) -> Self {
+        Self::new(vec![opts])
+    }
+}
+
+/// Store `preset` under `name`, replacing any earlier registration.
+pub fn register_preset(name: impl Into<String>, preset: impl Into<Preset>) {
+    if let Ok(mut registry) = REGISTRY.lock() {
+        registry.insert(name.into(), preset.into());
+    }
+}
+
+/// Remove a registered preset. Built-ins cannot be removed; returns whether
+/// a registration existed.
+pub fn unregister_preset(name: &str) -> bool {
+    REGISTRY
+        .lock()
+        .is_ok_and(|mut registry| registry.remove(name).is_some())
+}
+
+/// Look up a preset by name: registered presets first, then built-ins.
+#[must_use]
+pub fn preset(name: &str) -> Option<Preset> {
+    let registered = REGISTRY
+        .lock()
+        .ok()
+        .and_then(|registry| registry.get(name).cloned());
+    registered.or_else(|| Preset::builtin(name))
+}
+
+/// Every available preset name, sorted.
+#[must_use]
+pub fn preset_names() -> Vec<String> {
+    let mut names: Vec<String> = BUILTIN_PRESETS.iter().map(ToString::to_string).collect();
+    if let Ok(registry) = REGISTRY.lock() {
+        names.extend(registry.keys().cloned());
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtins_are_always_available() {
+        for name in BUILTIN_PRESETS {
+            assert!(preset(name).is_some(), "{name}");
+        }
+        assert_eq!(Preset::celebration().bursts().len(), 2);
+        assert!(preset("no-such-preset").is_none());
+    }
+
+    #[test]
+    fn registered_presets_are_found_and_listed() {
+        let brand = ConfettiOptions {
+            particle_count: 7,
+            colors: vec![Color::MAGENTA],
+            ..Default::default()
+        };
+        register_preset("test-brand", brand);
+        let found = preset("test-brand").expect("registered");
+        assert_eq!(found.bursts()[0].particle_count, 7);
+        assert!(preset_names().contains(&"test-brand".to_string()));
+        assert!(unregister_preset("test-brand"));
+        assert!(preset("test-brand").is_none());
+    }
+
+    #[test]
+    fn registration_overrides_builtin() {
+        register_preset("snow", Preset::cannon());
+        let found = preset("snow").expect("registered");
+        assert_eq!(found.bursts()[0].particle_count, 150);
+        assert!(unregister_preset("snow"));
+        let builtin = preset("snow").expect("builtin");
+        assert_eq!(builtin.bursts()[0].particle_count, 50);
+        assert!(!unregister_preset("snow"));
+    }
+}
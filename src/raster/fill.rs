@@ -0,0 +1,72 @@
+//! Scanline polygon filling with the non-zero winding rule, matching how a
+//! 2D canvas fills a path.
+
+/// Call `span(y, x_start, x_end)` for every run of pixels in row `y` whose
+/// centres fall inside the closed polylines `paths`, filled together so
+/// overlaps and holes follow the winding rule. Rows and columns are clipped
+/// to `width` x `height`.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+pub fn paths<P: AsRef<[(f64, f64)]>>(
+    paths: &[P],
+    width: u32,
+    height: u32,
+    mut span: impl FnMut(u32, u32, u32),
+) {
+    let polygons: Vec<&[(f64, f64)]> = paths
+        .iter()
+        .map(AsRef::as_ref)
+        .filter(|p| p.len() >= 3)
+        .collect();
+    if polygons.is_empty() {
+        return;
+    }
+    let (top, bottom) = polygons
+        .iter()
+        .flat_map(|p| p.iter())
+        .fold((f64::MAX, f64::MIN), |(lo, hi), p| {
+            (lo.min(p.1), hi.max(p.1))
+        });
+    let first_row = top.floor().max(0.0) as u32;
+    let last_row = bottom.ceil().min(f64::from(height)) as u32;
+    let mut crossings = Vec::new();
+
+    for row in first_row..last_row {
+        let yc = f64::from(row) + 0.5;
+        crossings.clear();
+        for polygon in &polygons {
+            collect_crossings(polygon, yc, &mut crossings);
+        }
+        crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut winding = 0;
+        for pair in crossings.windows(2) {
+            winding += pair[0].1;
+            if winding == 0 {
+                continue;
+            }
+            let start = (pair[0].0 - 0.5).ceil().clamp(0.0, f64::from(width)) as u32;
+            let end = (pair[1].0 - 0.5).ceil().clamp(0.0, f64::from(width)) as u32;
+            if start < end {
+                span(row, start, end);
+            }
+        }
+    }
+}
+
+/// Push `(x, direction)` for each edge crossing the horizontal line `y`.
+fn collect_crossings(polygon: &[(f64, f64)], y: f64, out: &mut Vec<(f64, i32)>) {
+    for (i, &(x0, y0)) in polygon.iter().enumerate() {
+        let (x1, y1) = polygon[(i + 1) % polygon.len()];
+        let direction = match (y0 <= y, y1 <= y) {
+            (true, false) => 1,
+            (false, true) => -1,
+            _ => continue,
+        };
+        let t = (y - y0) / (y1 - y0);
+        out.push((x0 + t * (x1 - x0), direction));
+    }
+}
@@ -0,0 +1,207 @@
+//! In-memory RGBA image.
+
+use super::fill;
+use crate::sim::Particle;
+use crate::types::{Color, ImageShape, Shape};
+
+/// An RGBA8 image with straight (non-premultiplied) alpha, row-major from the
+/// top-left corner.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Frame {
+    /// A fully transparent frame.
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    #[must_use]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[must_use]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Raw RGBA bytes.
+    #[must_use]
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    #[must_use]
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    /// The RGBA value at `(x, y)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are outside the frame.
+    #[must_use]
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = self.index(x, y);
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    /// Fill the whole frame with a color, replacing what was there.
+    pub fn fill(&mut self, color: Color) {
+        for px in self.pixels.chunks_exact_mut(4) {
+            px.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+
+    /// Reset every pixel to transparent.
+    pub fn clear(&mut self) {
+        self.pixels.fill(0);
+    }
+
+    /// Draw one particle with source-over blending.
+    pub fn draw(&mut self, p: &Particle) {
+        self.draw_scaled(p, 1.0);
+    }
+
+    /// Draw one particle with its coordinates multiplied by `scale`.
+    pub fn draw_scaled(&mut self, p: &Particle, scale: f64) {
+        let color = p.color();
+        let alpha = (p.opacity() * color.alpha()).clamp(0.0, 1.0);
+        let outlines: Vec<Vec<(f64, f64)>> = p
+            .outlines()
+            .into_iter()
+            .map(|outline| {
+                outline
+                    .into_iter()
+                    .map(|(x, y)| (x * scale, y * scale))
+                    .collect()
+            })
+            .collect();
+        let (width, height) = (self.width, self.height);
+        if let Shape::Image(image) = p.shape() {
+            if image.pixels().is_some() {
+                let m = p.unit_transform().map(|v| v * scale);
+                let fade = p.opacity();
+                fill::paths(&outlines, width, height, |y, x0, x1| {
+                    for x in x0..x1 {
+                        self.blend_texel(x, y, image, &m, fade);
+                    }
+                });
+                return;
+            }
+        }
+        fill::paths(&outlines, width, height, |y, x0, x1| {
+            for x in x0..x1 {
+                self.blend(x, y, color, alpha);
+            }
+        });
+    }
+
+    /// Blend the image texel under pixel `(x, y)`, found by inverting the
+    /// unit-box transform `m`, at opacity `fade`.
+    fn blend_texel(&mut self, x: u32, y: u32, image: &ImageShape, m: &[f64; 6], fade: f64) {
+        let det = m[0] * m[3] - m[1] * m[2];
+        if det.abs() < f64::EPSILON {
+            return;
+        }
+        let dx = f64::from(x) + 0.5 - m[4];
+        let dy = f64::from(y) + 0.5 - m[5];
+        let ux = (m[3] * dx - m[2] * dy) / det;
+        let uy = (m[0] * dy - m[1] * dx) / det;
+        if let Some(texel) = image.sample(ux, uy) {
+            let alpha = fade * f64::from(texel[3]) / 255.0;
+            self.blend(x, y, Color::rgba(texel[0], texel[1], texel[2], 255), alpha);
+        }
+    }
+
+    /// Shrink by `factor` in each direction, averaging each block of pixels
+    /// with alpha weighting so transparent samples do not darken edges.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn downsample(&self, factor: u32) -> Self {
+        let mut out = Self::new(self.width / factor, self.height / factor);
+        let samples = f64::from(factor * factor);
+        for y in 0..out.height {
+            for x in 0..out.width {
+                let mut sum = [0.0; 4];
+                for (sx, sy) in block(x, y, factor) {
+                    let px = self.pixel(sx, sy);
+                    let a = f64::from(px[3]);
+                    for c in 0..3 {
+                        sum[c] += f64::from(px[c]) * a;
+                    }
+                    sum[3] += a;
+                }
+                let i = out.index(x, y);
+                if sum[3] > 0.0 {
+                    for c in 0..3 {
+                        out.pixels[i + c] = (sum[c] / sum[3]).round() as u8;
+                    }
+                }
+                out.pixels[i + 3] = (sum[3] / samples).round() as u8;
+            }
+        }
+        out
+    }
+
+    /// Composite `color` at opacity `alpha` over the pixel at `(x, y)`.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn blend(&mut self, x: u32, y: u32, color: Color, alpha: f64) {
+        let i = self.index(x, y);
+        let dst_a = f64::from(self.pixels[i + 3]) / 255.0;
+        let out_a = alpha + dst_a * (1.0 - alpha);
+        if out_a <= 0.0 {
+            return;
+        }
+        for (c, src) in [color.r, color.g, color.b].into_iter().enumerate() {
+            let dst = f64::from(self.pixels[i + c]);
+            let mixed = (f64::from(src) * alpha + dst * dst_a * (1.0 - alpha)) / out_a;
+            self.pixels[i + c] = mixed.round() as u8;
+        }
+        self.pixels[i + 3] = (out_a * 255.0).round() as u8;
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * 4
+    }
+}
+
+/// Source pixel coordinates covered by output pixel `(x, y)`.
+fn block(x: u32, y: u32, factor: u32) -> impl Iterator<Item = (u32, u32)> {
+    (0..factor).flat_map(move |dy| (0..factor).map(move |dx| (x * factor + dx, y * factor + dy)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_frame_is_transparent() {
+        let frame = Frame::new(4, 3);
+        assert_eq!(frame.pixels().len(), 48);
+        assert!(frame.pixels().iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn downsample_averages_coverage() {
+        let mut frame = Frame::new(2, 2);
+        frame.pixels[..4].copy_from_slice(&[255, 0, 0, 255]);
+        let small = frame.downsample(2);
+        assert_eq!(small.pixel(0, 0), [255, 0, 0, 64]);
+    }
+}
@@ -0,0 +1,143 @@
+//! Headless CPU rasterizer.
+//!
+//! Draws simulation particles into in-memory RGBA buffers, with no browser
+//! canvas or GPU involved. Useful for previews on CI machines and for
+//! golden-image tests.
+
+mod fill;
+mod frame;
+mod source;
+
+pub use frame::Frame;
+pub use source::FrameSource;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Color, ConfettiOptions, Shape};
+
+    #[test]
+    fn fill_polygon_covers_pixel_centres() {
+        let square = [(1.0, 1.0), (3.0, 1.0), (3.0, 3.0), (1.0, 3.0)];
+        let mut covered = Vec::new();
+        fill::paths(&[square], 5, 5, |y, x0, x1| covered.push((y, x0, x1)));
+        assert_eq!(covered, vec![(1, 1, 3), (2, 1, 3)]);
+    }
+
+    #[test]
+    fn fill_polygon_clips_to_frame() {
+        let big = [(-5.0, -5.0), (50.0, -5.0), (50.0, 50.0), (-5.0, 50.0)];
+        let mut rows = 0;
+        fill::paths(&[big], 4, 2, |_, x0, x1| {
+            assert_eq!((x0, x1), (0, 4));
+            rows += 1;
+        });
+        assert_eq!(rows, 2);
+    }
+
+    #[test]
+    fn fill_paths_leaves_holes() {
+        let outer = vec![(0.0, 0.0), (6.0, 0.0), (6.0, 6.0), (0.0, 6.0)];
+        let hole = vec![(2.0, 2.0), (2.0, 4.0), (4.0, 4.0), (4.0, 2.0)];
+        let mut row_three = Vec::new();
+        fill::paths(&[outer, hole], 6, 6, |y, x0, x1| {
+            if y == 3 {
+                row_three.push((x0, x1));
+            }
+        });
+        assert_eq!(row_three, vec![(0, 2), (4, 6)]);
+    }
+
+    #[test]
+    fn path_shapes_are_rasterized() {
+        let opts = ConfettiOptions {
+            shapes: vec![Shape::path("M0 0 L10 0 L5 10 Z").expect("path")],
+            seed: Some(5),
+            flat: true,
+            start_velocity: 0.0,
+            ..Default::default()
+        };
+        let mut source = FrameSource::new(64, 64);
+        source.spawn(&opts);
+        let frame = source.next().expect("frame");
+        assert!(frame.pixels().chunks(4).any(|px| px[3] > 0));
+    }
+
+    #[test]
+    fn image_shapes_draw_their_pixels() {
+        let green = [0, 255, 0, 255].repeat(4);
+        let opts = ConfettiOptions {
+            shapes: vec![Shape::image(2, 2, green).expect("image")],
+            colors: vec![Color::RED],
+            seed: Some(5),
+            flat: true,
+            start_velocity: 0.0,
+            scalar: 2.0,
+            ..Default::default()
+        };
+        let mut source = FrameSource::new(64, 64);
+        source.spawn(&opts);
+        let frame = source.next().expect("frame");
+        let drawn: Vec<&[u8]> = frame.pixels().chunks(4).filter(|px| px[3] > 0).collect();
+        assert!(!drawn.is_empty());
+        assert!(drawn.iter().all(|px| px[0] == 0 && px[1] == 255));
+    }
+
+    #[test]
+    fn seeded_frames_are_reproducible() {
+        let opts = ConfettiOptions {
+            seed: Some(99),
+            ticks: 20,
+            ..Default::default()
+        };
+        let render = || {
+            let mut source = FrameSource::new(64, 64);
+            source.spawn(&opts);
+            source.collect::<Vec<_>>()
+        };
+        let (a, b) = (render(), render());
+        assert_eq!(a.len(), 19);
+        assert_eq!(a, b);
+        assert!(a[0].pixels().iter().any(|&v| v != 0));
+    }
+
+    #[test]
+    fn invalid_frame_rates_are_ignored() {
+        for fps in [0.0, -30.0, f64::NAN, f64::INFINITY] {
+            let source = FrameSource::new(10, 10).with_fps(24.0).with_fps(fps);
+            assert!((source.fps() - 24.0).abs() < f64::EPSILON);
+        }
+    }
+
+    #[test]
+    fn low_frame_rates_keep_real_time() {
+        let opts = ConfettiOptions {
+            seed: Some(3),
+            ticks: 120,
+            ..Default::default()
+        };
+        let frames = |fps| {
+            let mut source = FrameSource::new(16, 16).with_fps(fps);
+            source.spawn(&opts);
+            source.count()
+        };
+        // One 2 fps frame covers 30 ticks; clamping made it 15.
+        assert!(frames(2.0) <= frames(60.0) / 30 + 1);
+    }
+
+    #[test]
+    fn supersampling_keeps_output_size() {
+        let mut source = FrameSource::new(40, 30).with_supersampling(3);
+        source.spawn(&ConfettiOptions::default());
+        let frame = source.next().expect("first frame");
+        assert_eq!((frame.width(), frame.height()), (40, 30));
+    }
+
+    #[test]
+    fn background_is_opaque() {
+        let mut source = FrameSource::new(200, 200).with_background(Color::BLUE);
+        source.spawn(&ConfettiOptions::default());
+        let frame = source.next().expect("first frame");
+        assert_eq!(frame.pixel(0, 0), [0, 0, 255, 255]);
+    }
+}
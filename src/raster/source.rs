@@ -0,0 +1,109 @@
+//! Offscreen frame generation for a burst.
+
+use super::Frame;
+use crate::sim::{self, Particle, Simulation};
+use crate::types::{Color, ConfettiOptions};
+
+/// Steps a [`Simulation`] at a fixed frame rate and yields one rendered
+/// [`Frame`] per step until every particle has expired.
+pub struct FrameSource {
+    sim: Simulation,
+    width: u32,
+    height: u32,
+    fps: f64,
+    background: Option<Color>,
+    supersample: u32,
+}
+
+impl FrameSource {
+    /// A `width` x `height` transparent canvas rendered at 60 frames per second.
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            sim: Simulation::new(sim::std_entropy),
+            width,
+            height,
+            fps: 60.0,
+            background: None,
+            supersample: 1,
+        }
+    }
+
+    /// Frames per second of output; the physics still runs at 60 Hz and is
+    /// interpolated in between.
+    ///
+    /// A rate that is not finite and positive would never advance, so it is
+    /// ignored and the previous rate kept.
+    #[must_use]
+    pub fn with_fps(mut self, fps: f64) -> Self {
+        if fps.is_finite() && fps > 0.0 {
+            self.fps = fps;
+        }
+        self
+    }
+
+    /// Paint an opaque background behind the particles.
+    #[must_use]
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Render at `factor` times the resolution in each direction and average
+    /// down, for smoother edges. A factor of 1 disables supersampling.
+    #[must_use]
+    pub fn with_supersampling(mut self, factor: u32) -> Self {
+        self.supersample = factor.max(1);
+        self
+    }
+
+    /// Add a burst to the frames still to come.
+    pub fn spawn(&mut self, opts: &ConfettiOptions) {
+        self.sim
+            .spawn(opts, f64::from(self.width), f64::from(self.height));
+    }
+
+    #[must_use]
+    pub fn fps(&self) -> f64 {
+        self.fps
+    }
+
+    /// Advance by one output frame without rendering it.
+    ///
+    /// Returns `false` once every particle has expired.
+    pub fn advance(&mut self) -> bool {
+        !self.sim.is_empty() && self.sim.advance_unclamped(1.0 / self.fps)
+    }
+
+    /// Particles as they are drawn in the current frame, for exporters that
+    /// do not rasterize.
+    #[must_use]
+    pub fn particles(&self) -> Vec<Particle> {
+        self.sim.frame()
+    }
+
+    /// Render the current state without advancing it.
+    #[must_use]
+    pub fn render(&self) -> Frame {
+        let k = self.supersample;
+        let mut frame = Frame::new(self.width * k, self.height * k);
+        if let Some(color) = self.background {
+            frame.fill(color);
+        }
+        for p in &self.sim.frame() {
+            frame.draw_scaled(p, f64::from(k));
+        }
+        if k > 1 {
+            frame = frame.downsample(k);
+        }
+        frame
+    }
+}
+
+impl Iterator for FrameSource {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Frame> {
+        self.advance().then(|| self.render())
+    }
+}
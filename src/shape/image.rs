@@ -0,0 +1,209 @@
+//! Bitmap sprites as confetti shapes.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Why an image could not be used as a shape.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImageError {
+    /// Width or height is zero.
+    InvalidSize { width: u32, height: u32 },
+    /// The pixel buffer is not `width * height * 4` bytes long.
+    LengthMismatch { expected: usize, actual: usize },
+    /// A browser image has not finished loading, so its size is unknown.
+    NotLoaded,
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSize { width, height } => {
+                write!(f, "invalid image size {width}x{height}")
+            }
+            Self::LengthMismatch { expected, actual } => {
+                write!(f, "expected {expected} bytes of RGBA pixels, got {actual}")
+            }
+            Self::NotLoaded => f.write_str("image has not finished loading"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+/// A sprite drawn in place of a particle's square, with the same rotation,
+/// 3D flip and fade.
+///
+/// The image is scaled so its longer side spans the particle. Built from
+/// straight-alpha RGBA bytes on every platform, or from an
+/// `HtmlImageElement` / `ImageBitmap` in the browser. Cloning is cheap.
+#[derive(Clone)]
+pub struct ImageShape(Arc<ImageData>);
+
+pub(crate) struct ImageData {
+    id: u64,
+    width: u32,
+    height: u32,
+    pixels: Option<Vec<u8>>,
+}
+
+impl ImageShape {
+    /// Sprite from `width * height` RGBA pixels, row by row.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImageError`] if the size is zero or the buffer length does
+    /// not match it.
+    pub fn from_rgba(width: u32, height: u32, pixels: Vec<u8>) -> Result<Self, ImageError> {
+        let expected = width as usize * height as usize * 4;
+        if expected != pixels.len() {
+            return Err(ImageError::LengthMismatch {
+                expected,
+                actual: pixels.len(),
+            });
+        }
+        Self::new(width, height, Some(pixels))
+    }
+
+    /// Sprite whose pixels live outside Rust, e.g. in a browser image.
+    pub(crate) fn new(
+        width: u32,
+        height: u32,
+        pixels: Option<Vec<u8>>,
+    ) -> Result<Self, ImageError> {
+        if width == 0 || height == 0 {
+            return Err(ImageError::InvalidSize { width, height });
+        }
+        Ok(Self(Arc::new(ImageData {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            width,
+            height,
+            pixels,
+        })))
+    }
+
+    /// Identifier unique to this image, shared by its clones.
+    #[must_use]
+    pub fn id(&self) -> u64 {
+        self.0.id
+    }
+
+    #[must_use]
+    pub fn size(&self) -> (u32, u32) {
+        (self.0.width, self.0.height)
+    }
+
+    /// RGBA pixels, or `None` for browser images.
+    #[must_use]
+    pub fn pixels(&self) -> Option<&[u8]> {
+        self.0.pixels.as_deref()
+    }
+
+    /// Half-extents of the image in the unit box, with the longer side
+    /// spanning `-0.5..=0.5`.
+    #[must_use]
+    pub fn half_extents(&self) -> (f64, f64) {
+        let (w, h) = (f64::from(self.0.width), f64::from(self.0.height));
+        let side = w.max(h);
+        (w / side * 0.5, h / side * 0.5)
+    }
+
+    /// RGBA of the pixel under unit-box point `(x, y)`, or `None` outside
+    /// the image or for browser images.
+    #[must_use]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn sample(&self, x: f64, y: f64) -> Option<[u8; 4]> {
+        let pixels = self.pixels()?;
+        let (hw, hh) = self.half_extents();
+        let u = (x + hw) / (2.0 * hw);
+        let v = (y + hh) / (2.0 * hh);
+        if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+            return None;
+        }
+        let (width, height) = self.size();
+        let px = (u * f64::from(width)) as usize;
+        let py = (v * f64::from(height)) as usize;
+        let offset = (py * width as usize + px) * 4;
+        pixels.get(offset..offset + 4)?.try_into().ok()
+    }
+}
+
+#[cfg(feature = "web")]
+impl Drop for ImageData {
+    fn drop(&mut self) {
+        crate::images::forget(self.id);
+    }
+}
+
+impl PartialEq for ImageShape {
+    fn eq(&self, other: &Self) -> bool {
+        self.id() == other.id()
+    }
+}
+
+impl fmt::Debug for ImageShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImageShape")
+            .field("id", &self.0.id)
+            .field("width", &self.0.width)
+            .field("height", &self.0.height)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checker() -> ImageShape {
+        let mut pixels = Vec::new();
+        for i in 0..8 {
+            pixels.extend(if i % 2 == 0 {
+                [255, 0, 0, 255]
+            } else {
+                [0, 0, 255, 255]
+            });
+        }
+        ImageShape::from_rgba(4, 2, pixels).expect("image")
+    }
+
+    #[test]
+    fn rejects_bad_buffers() {
+        assert_eq!(
+            ImageShape::from_rgba(2, 2, vec![0; 15]),
+            Err(ImageError::LengthMismatch {
+                expected: 16,
+                actual: 15
+            })
+        );
+        assert_eq!(
+            ImageShape::from_rgba(0, 2, Vec::new()),
+            Err(ImageError::InvalidSize {
+                width: 0,
+                height: 2
+            })
+        );
+    }
+
+    #[test]
+    fn samples_in_unit_box() {
+        let image = checker();
+        assert_eq!(image.half_extents(), (0.5, 0.25));
+        assert_eq!(image.sample(-0.45, -0.2), Some([255, 0, 0, 255]));
+        assert_eq!(image.sample(-0.2, -0.2), Some([0, 0, 255, 255]));
+        assert_eq!(image.sample(0.0, 0.3), None);
+    }
+
+    #[test]
+    fn clones_share_identity() {
+        let image = checker();
+        assert_eq!(image.clone(), image);
+        assert_ne!(checker(), image);
+    }
+}
@@ -0,0 +1,125 @@
+//! Particle shapes.
+
+mod image;
+mod outline;
+mod path;
+mod svg;
+
+pub use image::{ImageError, ImageShape};
+pub use path::{PathError, PathShape};
+
+/// Font family used by [`Shape::text`].
+pub const DEFAULT_FONT: &str = "sans-serif";
+
+/// Shape of confetti particles
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Shape {
+    #[default]
+    Square,
+    Circle,
+    Star,
+    Triangle,
+    Diamond,
+    Hexagon,
+    Heart,
+    Crescent,
+    /// A streamer: a chain of linked points trailing the particle, drawn as a
+    /// strip that curls behind it and twists as it falls.
+    Ribbon,
+    /// A custom outline from SVG path data, drawn with the same tilt and
+    /// wobble as squares.
+    #[cfg_attr(feature = "serde", serde(with = "path_string"))]
+    Path(PathShape),
+    /// Text or emoji in a CSS font family, rotating and flipping like the
+    /// other shapes. `scalar` multiplies the size relative to a square.
+    ///
+    /// Glyphs need a browser to render; the desktop and headless renderers
+    /// draw text particles as squares in the particle color.
+    Text {
+        text: String,
+        #[cfg_attr(feature = "serde", serde(default = "default_font"))]
+        font: String,
+        #[cfg_attr(feature = "serde", serde(default = "default_text_scalar"))]
+        scalar: f64,
+    },
+    /// A bitmap sprite, such as a coin or sticker, drawn with the particle's
+    /// rotation, 3D flip and fade. Images cannot be serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Image(ImageShape),
+}
+
+impl Shape {
+    /// Shape from the `d` attribute of an SVG `<path>`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PathError`] if the path data is malformed or has no area.
+    pub fn path(d: &str) -> Result<Self, PathError> {
+        PathShape::parse(d).map(Self::Path)
+    }
+
+    /// Sprite shape from straight-alpha RGBA pixels, row by row.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`ImageError`] if the size is zero or does not match the
+    /// buffer length.
+    pub fn image(width: u32, height: u32, rgba: Vec<u8>) -> Result<Self, ImageError> {
+        ImageShape::from_rgba(width, height, rgba).map(Self::Image)
+    }
+
+    /// Outline of a built-in polygon shape in the unit box (`-0.5..=0.5`,
+    /// y down), or `None` for squares, circles and shapes with their own
+    /// geometry. The star reaches past the box.
+    #[must_use]
+    pub fn unit_outline(&self) -> Option<Vec<(f64, f64)>> {
+        match self {
+            Self::Star => Some(outline::star()),
+            Self::Triangle => Some(outline::triangle()),
+            Self::Diamond => Some(outline::diamond()),
+            Self::Hexagon => Some(outline::hexagon()),
+            Self::Heart => Some(outline::heart()),
+            Self::Crescent => Some(outline::crescent()),
+            _ => None,
+        }
+    }
+
+    /// Text or emoji shape in the [`DEFAULT_FONT`] at normal size.
+    #[must_use]
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text {
+            text: text.into(),
+            font: default_font(),
+            scalar: default_text_scalar(),
+        }
+    }
+}
+
+fn default_font() -> String {
+    DEFAULT_FONT.to_string()
+}
+
+fn default_text_scalar() -> f64 {
+    1.0
+}
+
+/// Serialize path shapes as their `d` string.
+#[cfg(feature = "serde")]
+mod path_string {
+    use super::PathShape;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(path: &PathShape, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(path.source())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PathShape, D::Error> {
+        let d = String::deserialize(deserializer)?;
+        PathShape::parse(&d).map_err(de::Error::custom)
+    }
+}
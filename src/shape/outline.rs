@@ -0,0 +1,117 @@
+//! Unit-box outlines of the built-in polygon shapes.
+//!
+//! Every outline is centred on the origin and sized against the
+//! `-0.5..=0.5` box that [`Particle::unit_transform`](crate::sim::Particle::unit_transform)
+//! maps onto the particle, so the shapes tilt and flip exactly like squares.
+
+use libm::{atan2, cos, sin, sqrt};
+use std::f64::consts::{PI, TAU};
+
+/// Segments used for curved outlines.
+const CURVE_SEGMENTS: u32 = 32;
+
+/// Five-pointed star with its first point straight up. It reaches past the
+/// unit box to keep the size stars had before they tilted.
+pub fn star() -> Vec<(f64, f64)> {
+    let step = PI / 5.0;
+    (0..10)
+        .map(|i| {
+            let rot = PI / 2.0 * 3.0 + step * f64::from(i);
+            let r = if i % 2 == 0 { 0.8 } else { 0.4 };
+            (cos(rot) * r, sin(rot) * r)
+        })
+        .collect()
+}
+
+/// Triangle pointing up.
+pub fn triangle() -> Vec<(f64, f64)> {
+    vec![(0.0, -0.5), (0.5, 0.5), (-0.5, 0.5)]
+}
+
+/// Rhombus, taller than it is wide.
+pub fn diamond() -> Vec<(f64, f64)> {
+    vec![(0.0, -0.5), (0.35, 0.0), (0.0, 0.5), (-0.35, 0.0)]
+}
+
+/// Regular hexagon with flat top and bottom.
+pub fn hexagon() -> Vec<(f64, f64)> {
+    (0..6)
+        .map(|i| {
+            let a = PI / 3.0 * f64::from(i);
+            (0.5 * cos(a), 0.5 * sin(a))
+        })
+        .collect()
+}
+
+/// Classic parametric heart, scaled to the unit box.
+pub fn heart() -> Vec<(f64, f64)> {
+    (0..CURVE_SEGMENTS)
+        .map(|i| {
+            let t = TAU * f64::from(i) / f64::from(CURVE_SEGMENTS);
+            let s = sin(t);
+            let x = 16.0 * s * s * s;
+            let y = 13.0 * cos(t) - 5.0 * cos(2.0 * t) - 2.0 * cos(3.0 * t) - cos(4.0 * t);
+            // The curve spans x in ±16 and y in -17..≈12, upside down.
+            (x / 32.0, (-y - 2.5) / 32.0)
+        })
+        .collect()
+}
+
+/// Crescent moon opening to the right: a circle with an offset circle cut
+/// out of it.
+pub fn crescent() -> Vec<(f64, f64)> {
+    const OUTER: f64 = 0.5;
+    const INNER: f64 = 0.4;
+    const OFFSET: f64 = 0.25;
+    // Where the two circles cross, mirrored about the x axis.
+    let x = (OFFSET * OFFSET + OUTER * OUTER - INNER * INNER) / (2.0 * OFFSET);
+    let y = sqrt(OUTER * OUTER - x * x);
+    let outer_start = atan2(y, x);
+    let inner_start = atan2(-y, x - OFFSET);
+    let inner_end = atan2(y, x - OFFSET) - TAU;
+    let n = f64::from(CURVE_SEGMENTS);
+    let outer = (0..=CURVE_SEGMENTS).map(|i| {
+        let a = outer_start + (TAU - 2.0 * outer_start) * f64::from(i) / n;
+        (OUTER * cos(a), OUTER * sin(a))
+    });
+    let inner = (1..CURVE_SEGMENTS).map(|i| {
+        let a = inner_start + (inner_end - inner_start) * f64::from(i) / n;
+        (OFFSET + INNER * cos(a), INNER * sin(a))
+    });
+    outer.chain(inner).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Signed shoelace area.
+    fn area(points: &[(f64, f64)]) -> f64 {
+        let n = points.len();
+        (0..n)
+            .map(|i| {
+                let (a, b) = (points[i], points[(i + 1) % n]);
+                a.0 * b.1 - b.0 * a.1
+            })
+            .sum::<f64>()
+            / 2.0
+    }
+
+    #[test]
+    fn outlines_fit_the_unit_box() {
+        for outline in [triangle(), diamond(), hexagon(), heart(), crescent()] {
+            assert!(outline
+                .iter()
+                .all(|&(x, y)| x.abs() <= 0.5 + 1e-9 && y.abs() <= 0.5 + 1e-9));
+            assert!(area(&outline).abs() > 0.05);
+        }
+    }
+
+    #[test]
+    fn crescent_is_cut_out() {
+        let full = PI * 0.25;
+        let crescent = area(&crescent()).abs();
+        assert!(crescent < full * 0.6);
+        assert!(crescent > full * 0.2);
+    }
+}
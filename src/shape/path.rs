@@ -0,0 +1,163 @@
+//! Confetti shapes defined by SVG path data.
+
+use super::svg;
+use std::fmt;
+use std::sync::Arc;
+
+/// Why an SVG path string could not be used as a shape.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathError {
+    /// The path draws nothing with an area.
+    Empty,
+    /// Unexpected input at this byte offset.
+    Syntax(usize),
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("path encloses no area"),
+            Self::Syntax(offset) => write!(f, "invalid path data at byte {offset}"),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+/// An SVG path parsed once and normalized to a unit box.
+///
+/// The longest side of the path's bounds is scaled to 1.0 and the box is
+/// centred on the origin, so every point lies within `-0.5..=0.5`. Curves
+/// and arcs are flattened to line segments. Cloning is cheap.
+#[derive(Clone)]
+pub struct PathShape(Arc<PathData>);
+
+struct PathData {
+    source: String,
+    subpaths: Vec<Vec<(f64, f64)>>,
+    centre: (f64, f64),
+    scale: f64,
+}
+
+impl PathShape {
+    /// Parse the `d` attribute of an SVG `<path>`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PathError::Syntax`] for malformed data, or
+    /// [`PathError::Empty`] if the path has no area.
+    pub fn parse(d: &str) -> Result<Self, PathError> {
+        let mut subpaths = svg::flatten(d)?;
+        let points = subpaths.iter().flatten();
+        let (min, max) = points.fold(
+            ((f64::MAX, f64::MAX), (f64::MIN, f64::MIN)),
+            |(lo, hi), p| {
+                (
+                    (lo.0.min(p.0), lo.1.min(p.1)),
+                    (hi.0.max(p.0), hi.1.max(p.1)),
+                )
+            },
+        );
+        let side = (max.0 - min.0).max(max.1 - min.1);
+        if subpaths.is_empty() || side <= 0.0 || !side.is_finite() {
+            return Err(PathError::Empty);
+        }
+        let centre = ((min.0 + max.0) * 0.5, (min.1 + max.1) * 0.5);
+        let scale = 1.0 / side;
+        for p in subpaths.iter_mut().flatten() {
+            *p = ((p.0 - centre.0) * scale, (p.1 - centre.1) * scale);
+        }
+        Ok(Self(Arc::new(PathData {
+            source: d.to_string(),
+            subpaths,
+            centre,
+            scale,
+        })))
+    }
+
+    /// The path data this shape was parsed from.
+    #[must_use]
+    pub fn source(&self) -> &str {
+        &self.0.source
+    }
+
+    /// Closed polylines in unit-box coordinates, filled with the non-zero
+    /// winding rule.
+    #[must_use]
+    pub fn subpaths(&self) -> &[Vec<(f64, f64)>] {
+        &self.0.subpaths
+    }
+
+    /// Centre and scale mapping source coordinates into the unit box:
+    /// `unit = (source - centre) * scale`.
+    #[must_use]
+    pub fn normalization(&self) -> ((f64, f64), f64) {
+        (self.0.centre, self.0.scale)
+    }
+}
+
+impl PartialEq for PathShape {
+    fn eq(&self, other: &Self) -> bool {
+        self.source() == other.source()
+    }
+}
+
+impl Eq for PathShape {}
+
+impl fmt::Debug for PathShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PathShape").field(&self.source()).finish()
+    }
+}
+
+impl std::str::FromStr for PathShape {
+    type Err = PathError;
+
+    fn from_str(d: &str) -> Result<Self, Self::Err> {
+        Self::parse(d)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn near(a: (f64, f64), b: (f64, f64)) -> bool {
+        (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9
+    }
+
+    #[test]
+    fn normalizes_to_unit_box() {
+        let path = PathShape::parse("M10 10 H30 V20 H10 Z").expect("parse");
+        let points = &path.subpaths()[0];
+        assert!(near(points[0], (-0.5, -0.25)));
+        assert!(near(points[2], (0.5, 0.25)));
+        assert_eq!(path.normalization(), ((20.0, 15.0), 0.05));
+    }
+
+    #[test]
+    fn relative_commands_and_implicit_lines() {
+        let path = PathShape::parse("m0 0 10 0 0 10z m20 0 l5 0 0 5 z").expect("parse");
+        assert_eq!(path.subpaths().len(), 2);
+        assert_eq!(path.subpaths()[0].len(), 3);
+    }
+
+    #[test]
+    fn curves_and_arcs_are_flattened() {
+        let heart = "M12 21C12 21 3 14 3 8.5A4.5 4.5 0 0112 6a4.5 4.5 0 019 2.5C21 14 12 21 12 21z";
+        let path = PathShape::parse(heart).expect("parse");
+        assert!(path.subpaths()[0].len() > 40);
+        let max = path.subpaths()[0]
+            .iter()
+            .fold(0.0_f64, |m, p| m.max(p.0.abs()).max(p.1.abs()));
+        assert!((max - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert_eq!(PathShape::parse("M0 0 L1 x"), Err(PathError::Syntax(8)));
+        assert_eq!(PathShape::parse("10 10"), Err(PathError::Syntax(0)));
+        assert_eq!(PathShape::parse("M0 0 L10 0"), Err(PathError::Empty));
+        assert_eq!(PathShape::parse(""), Err(PathError::Empty));
+    }
+}
@@ -0,0 +1,278 @@
+//! SVG path `d` parsing, flattened to polylines.
+
+use super::path::PathError;
+use libm::{atan2, cos, sin};
+use std::f64::consts::PI;
+
+/// Line segments used per Bezier curve.
+const CURVE_STEPS: usize = 16;
+
+/// Parse `d` into closed polylines, one per subpath, in source coordinates.
+pub fn flatten(d: &str) -> Result<Vec<Vec<(f64, f64)>>, PathError> {
+    let mut lexer = Lexer {
+        src: d.as_bytes(),
+        pos: 0,
+    };
+    let mut pen = Pen::default();
+    let mut command = None;
+    loop {
+        lexer.skip_separators();
+        let Some(&byte) = lexer.src.get(lexer.pos) else {
+            break;
+        };
+        if byte.is_ascii_alphabetic() {
+            lexer.pos += 1;
+            command = Some(byte);
+        } else if matches!(command, None | Some(b'Z' | b'z')) {
+            return Err(PathError::Syntax(lexer.pos));
+        }
+        let Some(current) = command else {
+            return Err(PathError::Syntax(lexer.pos));
+        };
+        pen.apply(current, &mut lexer)?;
+        // Extra coordinate pairs after a moveto are implicit linetos.
+        command = match current {
+            b'M' => Some(b'L'),
+            b'm' => Some(b'l'),
+            other => Some(other),
+        };
+    }
+    pen.finish();
+    Ok(pen.subpaths)
+}
+
+struct Lexer<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl Lexer<'_> {
+    fn skip_separators(&mut self) {
+        while self
+            .src
+            .get(self.pos)
+            .is_some_and(|b| b.is_ascii_whitespace() || *b == b',')
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, PathError> {
+        self.skip_separators();
+        let start = self.pos;
+        let at = |i: usize| self.src.get(i).copied().unwrap_or(0);
+        let mut end = start;
+        if matches!(at(end), b'+' | b'-') {
+            end += 1;
+        }
+        let mut seen_dot = false;
+        while at(end).is_ascii_digit() || (at(end) == b'.' && !seen_dot) {
+            seen_dot |= at(end) == b'.';
+            end += 1;
+        }
+        if matches!(at(end), b'e' | b'E') {
+            let mut exp = end + 1;
+            if matches!(at(exp), b'+' | b'-') {
+                exp += 1;
+            }
+            if at(exp).is_ascii_digit() {
+                end = exp;
+                while at(end).is_ascii_digit() {
+                    end += 1;
+                }
+            }
+        }
+        let text = std::str::from_utf8(&self.src[start..end]).unwrap_or("");
+        let value = text.parse::<f64>().map_err(|_| PathError::Syntax(start))?;
+        self.pos = end;
+        Ok(value)
+    }
+
+    /// Arc flags may be written without separators, e.g. `a1 1 0 01 1 1`.
+    fn flag(&mut self) -> Result<bool, PathError> {
+        self.skip_separators();
+        let flag = match self.src.get(self.pos) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(PathError::Syntax(self.pos)),
+        };
+        self.pos += 1;
+        Ok(flag)
+    }
+
+    fn point(&mut self) -> Result<(f64, f64), PathError> {
+        Ok((self.number()?, self.number()?))
+    }
+}
+
+/// Current position and the subpaths drawn so far.
+#[derive(Default)]
+struct Pen {
+    subpaths: Vec<Vec<(f64, f64)>>,
+    current: Vec<(f64, f64)>,
+    at: (f64, f64),
+    start: (f64, f64),
+    /// Reflected control point for a following `S` or `T`.
+    control: Option<(u8, (f64, f64))>,
+}
+
+impl Pen {
+    fn apply(&mut self, command: u8, lexer: &mut Lexer) -> Result<(), PathError> {
+        let relative = command.is_ascii_lowercase();
+        let origin = if relative { self.at } else { (0.0, 0.0) };
+        let offset = |p: (f64, f64)| (p.0 + origin.0, p.1 + origin.1);
+        let mut control = None;
+        match command.to_ascii_uppercase() {
+            b'M' => {
+                self.finish();
+                self.at = offset(lexer.point()?);
+                self.start = self.at;
+                self.current.push(self.at);
+            }
+            b'L' => self.line_to(offset(lexer.point()?)),
+            b'H' => self.line_to((lexer.number()? + origin.0, self.at.1)),
+            b'V' => self.line_to((self.at.0, lexer.number()? + origin.1)),
+            b'C' => {
+                let c1 = offset(lexer.point()?);
+                let c2 = offset(lexer.point()?);
+                self.cubic_to(c1, c2, offset(lexer.point()?));
+                control = Some((b'C', c2));
+            }
+            b'S' => {
+                let c1 = self.reflected(b'C');
+                let c2 = offset(lexer.point()?);
+                self.cubic_to(c1, c2, offset(lexer.point()?));
+                control = Some((b'C', c2));
+            }
+            b'Q' => {
+                let c = offset(lexer.point()?);
+                self.quad_to(c, offset(lexer.point()?));
+                control = Some((b'Q', c));
+            }
+            b'T' => {
+                let c = self.reflected(b'Q');
+                self.quad_to(c, offset(lexer.point()?));
+                control = Some((b'Q', c));
+            }
+            b'A' => {
+                let radii = lexer.point()?;
+                let rotation = lexer.number()?;
+                let flags = (lexer.flag()?, lexer.flag()?);
+                self.arc_to(radii, rotation, flags, offset(lexer.point()?));
+            }
+            b'Z' => {
+                self.finish();
+                self.at = self.start;
+            }
+            _ => return Err(PathError::Syntax(lexer.pos - 1)),
+        }
+        self.control = control;
+        Ok(())
+    }
+
+    /// Close the current subpath, keeping it if it encloses anything.
+    fn finish(&mut self) {
+        let points = std::mem::take(&mut self.current);
+        if points.len() >= 3 {
+            self.subpaths.push(points);
+        }
+    }
+
+    fn line_to(&mut self, to: (f64, f64)) {
+        if self.current.is_empty() {
+            self.current.push(self.at);
+        }
+        self.current.push(to);
+        self.at = to;
+    }
+
+    fn reflected(&self, kind: u8) -> (f64, f64) {
+        match self.control {
+            Some((k, c)) if k == kind => (2.0 * self.at.0 - c.0, 2.0 * self.at.1 - c.1),
+            _ => self.at,
+        }
+    }
+
+    // Bernstein weights and control points keep their textbook names.
+    #[allow(clippy::cast_precision_loss, clippy::many_single_char_names)]
+    fn cubic_to(&mut self, c1: (f64, f64), c2: (f64, f64), to: (f64, f64)) {
+        let p0 = self.at;
+        for i in 1..=CURVE_STEPS {
+            let t = i as f64 / CURVE_STEPS as f64;
+            let u = 1.0 - t;
+            let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+            self.line_to((
+                a * p0.0 + b * c1.0 + c * c2.0 + d * to.0,
+                a * p0.1 + b * c1.1 + c * c2.1 + d * to.1,
+            ));
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss, clippy::many_single_char_names)]
+    fn quad_to(&mut self, c: (f64, f64), to: (f64, f64)) {
+        let p0 = self.at;
+        for i in 1..=CURVE_STEPS {
+            let t = i as f64 / CURVE_STEPS as f64;
+            let u = 1.0 - t;
+            let (a, b, d) = (u * u, 2.0 * u * t, t * t);
+            self.line_to((a * p0.0 + b * c.0 + d * to.0, a * p0.1 + b * c.1 + d * to.1));
+        }
+    }
+
+    /// Elliptical arc, converted from SVG's endpoint form to a centre form.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    fn arc_to(&mut self, radii: (f64, f64), rotation: f64, flags: (bool, bool), to: (f64, f64)) {
+        let from = self.at;
+        let (mut rx, mut ry) = (radii.0.abs(), radii.1.abs());
+        if from == to {
+            return;
+        }
+        if rx == 0.0 || ry == 0.0 {
+            self.line_to(to);
+            return;
+        }
+        let phi = rotation.to_radians();
+        let (sp, cp) = (sin(phi), cos(phi));
+        let (hx, hy) = ((from.0 - to.0) / 2.0, (from.1 - to.1) / 2.0);
+        let (x1, y1) = (cp * hx + sp * hy, -sp * hx + cp * hy);
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+        let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let sign = if flags.0 == flags.1 { -1.0 } else { 1.0 };
+        let coef = sign * (num / den).max(0.0).sqrt();
+        let (cx1, cy1) = (coef * rx * y1 / ry, -coef * ry * x1 / rx);
+        let centre = (
+            cp * cx1 - sp * cy1 + f64::midpoint(from.0, to.0),
+            sp * cx1 + cp * cy1 + f64::midpoint(from.1, to.1),
+        );
+        let angle =
+            |ux: f64, uy: f64, vx: f64, vy: f64| atan2(ux * vy - uy * vx, ux * vx + uy * vy);
+        let (ux, uy) = ((x1 - cx1) / rx, (y1 - cy1) / ry);
+        let (vx, vy) = ((-x1 - cx1) / rx, (-y1 - cy1) / ry);
+        let start = angle(1.0, 0.0, ux, uy);
+        let mut sweep = angle(ux, uy, vx, vy);
+        if flags.1 && sweep < 0.0 {
+            sweep += 2.0 * PI;
+        } else if !flags.1 && sweep > 0.0 {
+            sweep -= 2.0 * PI;
+        }
+        let steps = ((sweep.abs() / (PI / 16.0)).ceil() as usize).max(1);
+        for i in 1..steps {
+            let t = start + sweep * i as f64 / steps as f64;
+            let (st, ct) = (sin(t), cos(t));
+            self.line_to((
+                centre.0 + rx * ct * cp - ry * st * sp,
+                centre.1 + rx * ct * sp + ry * st * cp,
+            ));
+        }
+        self.line_to(to);
+    }
+}
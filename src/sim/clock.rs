@@ -0,0 +1,82 @@
+//! Fixed-timestep clock decoupling physics from the display refresh rate.
+
+/// Length of one simulation tick, in seconds (60 Hz).
+pub const TICK_SECONDS: f64 = 1.0 / 60.0;
+
+/// Longest frame gap that is simulated; longer pauses (background tabs,
+/// debugger breaks) are clamped so the animation resumes instead of jumping.
+const MAX_FRAME_SECONDS: f64 = 0.25;
+
+/// Accumulates real elapsed time and converts it into whole ticks.
+#[derive(Clone, Debug, Default)]
+pub struct FixedStep {
+    accumulator: f64,
+    last: Option<f64>,
+}
+
+impl FixedStep {
+    /// Add `dt` seconds of real time and return how many ticks to run.
+    pub fn advance(&mut self, dt: f64) -> u32 {
+        self.advance_unclamped(dt.min(MAX_FRAME_SECONDS))
+    }
+
+    /// Like [`FixedStep::advance`], but simulating all of `dt` however long,
+    /// for offline rendering where a long frame is not a pause.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn advance_unclamped(&mut self, dt: f64) -> u32 {
+        self.accumulator += dt.max(0.0);
+        let ticks = (self.accumulator / TICK_SECONDS).floor();
+        self.accumulator -= ticks * TICK_SECONDS;
+        ticks as u32
+    }
+
+    /// Like [`FixedStep::advance`], but measured from the previous call's
+    /// timestamp. The first call runs exactly one tick.
+    pub fn advance_to(&mut self, now: f64) -> u32 {
+        let dt = self.last.map_or(TICK_SECONDS, |last| now - last);
+        self.last = Some(now);
+        self.advance(dt)
+    }
+
+    /// Fraction of a tick left over, for interpolating between states.
+    #[must_use]
+    pub fn alpha(&self) -> f64 {
+        self.accumulator / TICK_SECONDS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticks_follow_real_time_not_frames() {
+        let mut at_60 = FixedStep::default();
+        let mut at_144 = FixedStep::default();
+        let ticks_60: u32 = (0..60).map(|_| at_60.advance(1.0 / 60.0)).sum();
+        let ticks_144: u32 = (0..144).map(|_| at_144.advance(1.0 / 144.0)).sum();
+        assert!((59..=60).contains(&ticks_60));
+        assert!((59..=60).contains(&ticks_144));
+    }
+
+    #[test]
+    fn leftover_time_becomes_alpha() {
+        let mut clock = FixedStep::default();
+        assert_eq!(clock.advance(TICK_SECONDS * 2.5), 2);
+        assert!((clock.alpha() - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn long_pauses_are_clamped() {
+        let mut clock = FixedStep::default();
+        assert_eq!(clock.advance_to(0.0), 1);
+        assert_eq!(clock.advance_to(10.0), 15);
+    }
+
+    #[test]
+    fn unclamped_steps_keep_long_frames() {
+        let mut clock = FixedStep::default();
+        assert_eq!(clock.advance_unclamped(0.5), 30);
+        assert_eq!(clock.advance_unclamped(-1.0), 0);
+    }
+}
@@ -0,0 +1,29 @@
+//! Polygon outlines for particle shapes, shared by renderers that cannot
+//! draw curves natively.
+
+use libm::{cos, sin};
+use std::f64::consts::PI;
+
+/// Number of segments used to approximate an ellipse.
+const ELLIPSE_SEGMENTS: usize = 24;
+
+/// Rotated ellipse centred on `center` with radii `(rx, ry)`.
+#[allow(clippy::cast_precision_loss)]
+pub fn ellipse(center: (f64, f64), rx: f64, ry: f64, rotation: f64) -> Vec<(f64, f64)> {
+    let (rs, rc) = (sin(rotation), cos(rotation));
+    (0..ELLIPSE_SEGMENTS)
+        .map(|i| {
+            let t = 2.0 * PI * i as f64 / ELLIPSE_SEGMENTS as f64;
+            let (ex, ey) = (rx * cos(t), ry * sin(t));
+            (center.0 + ex * rc - ey * rs, center.1 + ex * rs + ey * rc)
+        })
+        .collect()
+}
+
+/// Map `p` through the affine transform `[a, b, c, d, e, f]`.
+pub fn apply(m: &[f64; 6], p: (f64, f64)) -> (f64, f64) {
+    (
+        m[0] * p.0 + m[2] * p.1 + m[4],
+        m[1] * p.0 + m[3] * p.1 + m[5],
+    )
+}
@@ -0,0 +1,497 @@
+//! Platform-agnostic simulation core.
+//!
+//! Owns spawning and stepping of particles. Renderers (2D canvas, wgpu) only
+//! draw what a [`Simulation`] produces, so every backend shares one physics
+//! model.
+
+mod clock;
+mod geometry;
+mod particle;
+mod pose;
+mod ribbon;
+mod rng;
+
+pub use clock::{FixedStep, TICK_SECONDS};
+pub use particle::Particle;
+pub use ribbon::RIBBON_POINTS;
+pub use rng::{std_entropy, Rng};
+
+use crate::types::ConfettiOptions;
+use std::sync::Arc;
+
+/// Platform entropy source yielding uniform numbers in `0.0..1.0`.
+///
+/// Only consulted to pick a seed for bursts whose `seed` is `None`.
+pub type RandomFn = fn() -> f64;
+
+/// Identifies the particles of one [`Simulation::spawn`] call.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BurstId(u64);
+
+/// A set of live particles plus the entropy used to seed unseeded bursts.
+pub struct Simulation {
+    particles: Vec<Particle>,
+    entropy: RandomFn,
+    clock: FixedStep,
+    next_id: u64,
+    next_burst: u64,
+}
+
+impl Simulation {
+    #[must_use]
+    pub fn new(entropy: RandomFn) -> Self {
+        Self {
+            particles: Vec::new(),
+            entropy,
+            clock: FixedStep::default(),
+            next_id: 0,
+            next_burst: 0,
+        }
+    }
+
+    /// Spawn a burst into a `width` x `height` surface, placed by `opts.origin`.
+    ///
+    /// With `opts.seed` set, the burst's trajectories are identical on every
+    /// platform for the same options and surface size.
+    ///
+    /// A burst with no `colors` or no `shapes` spawns nothing; use
+    /// [`ConfettiOptions::validate`] to report such options instead.
+    pub fn spawn(&mut self, opts: &ConfettiOptions, width: f64, height: f64) -> BurstId {
+        let burst = BurstId(self.next_burst);
+        self.next_burst += 1;
+        if opts.colors.is_empty() || opts.shapes.is_empty() {
+            return burst;
+        }
+        let start = (width * opts.origin.x, height * opts.origin.y);
+        let mut rng = Rng::new(opts.seed.unwrap_or_else(|| self.entropy_seed()));
+        let gradient = opts.gradient.clone().map(Arc::new);
+        for i in 0..opts.particle_count {
+            let color = opts.colors[i as usize % opts.colors.len()];
+            let shape = opts.shapes[rng.next_index(opts.shapes.len())].clone();
+            let particle_rng = Rng::new(rng.next_u64());
+            let mut particle = Particle::new(opts, start, color, shape, particle_rng);
+            if let Some(gradient) = &gradient {
+                particle = particle.with_gradient(Arc::clone(gradient));
+            }
+            self.particles
+                .push(particle.with_id(self.next_id).with_burst(burst));
+            self.next_id += 1;
+        }
+        burst
+    }
+
+    /// Remove every particle of `burst`, leaving other bursts running.
+    ///
+    /// Cancelling the last burst restarts the clock, as expiring does.
+    pub fn cancel(&mut self, burst: BurstId) {
+        self.particles.retain(|p| p.burst() != burst);
+        if self.particles.is_empty() {
+            self.clock = FixedStep::default();
+        }
+    }
+
+    /// Whether any particle of `burst` is still alive.
+    #[must_use]
+    pub fn contains(&self, burst: BurstId) -> bool {
+        self.particles.iter().any(|p| p.burst() == burst)
+    }
+
+    /// Advance every particle one tick, dropping expired ones.
+    ///
+    /// Returns `true` while any particle is still alive.
+    pub fn step(&mut self) -> bool {
+        self.particles.retain_mut(Particle::update);
+        !self.particles.is_empty()
+    }
+
+    /// Run as many fixed ticks as fit into `dt` seconds of real time, carrying
+    /// the remainder over to the next call.
+    ///
+    /// Returns `true` while any particle is still alive. Once everything has
+    /// expired the clock restarts, so an idle gap before the next burst is
+    /// not simulated.
+    pub fn advance(&mut self, dt: f64) -> bool {
+        let ticks = self.clock.advance(dt);
+        self.run(ticks)
+    }
+
+    /// Like [`Simulation::advance`], but without clamping long frames, for
+    /// offline renderers whose frames are further apart than a real-time
+    /// pause.
+    pub fn advance_unclamped(&mut self, dt: f64) -> bool {
+        let ticks = self.clock.advance_unclamped(dt);
+        self.run(ticks)
+    }
+
+    /// Like [`Simulation::advance`], but measured against a timestamp `now`
+    /// (in seconds, from any monotonic clock), so motion speed is independent
+    /// of the frame rate.
+    pub fn advance_to(&mut self, now: f64) -> bool {
+        let ticks = self.clock.advance_to(now);
+        self.run(ticks)
+    }
+
+    /// Particles at their last simulated tick.
+    #[must_use]
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// Particles blended between the last two ticks for smooth drawing on
+    /// displays faster than the tick rate.
+    #[must_use]
+    pub fn frame(&self) -> Vec<Particle> {
+        let alpha = self.clock.alpha();
+        self.particles
+            .iter()
+            .map(|p| p.interpolate(alpha))
+            .collect()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.clock = FixedStep::default();
+    }
+
+    fn run(&mut self, ticks: u32) -> bool {
+        for _ in 0..ticks {
+            if !self.step() {
+                self.clock = FixedStep::default();
+                return false;
+            }
+        }
+        !self.particles.is_empty()
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn entropy_seed(&self) -> u64 {
+        let word = |r: f64| (r * f64::from(u32::MAX)) as u64;
+        (word((self.entropy)()) << 32) | word((self.entropy)())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Color, Gradient, Origin, Shape};
+
+    fn half() -> f64 {
+        0.5
+    }
+
+    fn opts() -> ConfettiOptions {
+        ConfettiOptions {
+            particle_count: 10,
+            ticks: 3,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn spawn_places_particles_at_origin() {
+        let mut sim = Simulation::new(half);
+        let opts = ConfettiOptions {
+            origin: Origin { x: 0.25, y: 1.0 },
+            ..opts()
+        };
+        sim.spawn(&opts, 400.0, 300.0);
+        assert_eq!(sim.particles().len(), 10);
+        assert_eq!(sim.particles()[0].position(), (100.0, 300.0));
+    }
+
+    #[test]
+    fn spawn_cycles_colors_and_picks_shapes() {
+        let mut sim = Simulation::new(half);
+        let opts = ConfettiOptions {
+            shapes: vec![Shape::Star],
+            ..opts()
+        };
+        sim.spawn(&opts, 100.0, 100.0);
+        let colors = crate::types::default_colors();
+        assert_eq!(sim.particles()[1].color(), colors[1]);
+        assert_eq!(sim.particles()[7].color(), colors[0]);
+        assert_eq!(sim.particles()[0].shape(), &Shape::Star);
+    }
+
+    #[test]
+    fn spawn_ignores_empty_palettes() {
+        let mut sim = Simulation::new(half);
+        let no_colors = ConfettiOptions {
+            colors: vec![],
+            ..opts()
+        };
+        let no_shapes = ConfettiOptions {
+            shapes: vec![],
+            ..opts()
+        };
+        sim.spawn(&no_colors, 100.0, 100.0);
+        sim.spawn(&no_shapes, 100.0, 100.0);
+        assert!(sim.is_empty());
+    }
+
+    #[test]
+    fn particles_get_unique_ids() {
+        let mut sim = Simulation::new(half);
+        sim.spawn(&opts(), 100.0, 100.0);
+        sim.spawn(&opts(), 100.0, 100.0);
+        let ids: Vec<u64> = sim.particles().iter().map(Particle::id).collect();
+        assert_eq!(ids, (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn step_expires_particles_after_ticks() {
+        let mut sim = Simulation::new(half);
+        sim.spawn(&opts(), 100.0, 100.0);
+        assert!(sim.step());
+        assert!(sim.step());
+        assert!(!sim.step());
+        assert!(sim.is_empty());
+    }
+
+    #[test]
+    fn gravity_pulls_particles_down() {
+        let mut sim = Simulation::new(half);
+        let opts = ConfettiOptions {
+            start_velocity: 0.0,
+            ..opts()
+        };
+        sim.spawn(&opts, 100.0, 100.0);
+        sim.step();
+        let (x, y) = sim.particles()[0].position();
+        assert!((x - 50.0).abs() < f64::EPSILON);
+        assert!((y - 53.0).abs() < f64::EPSILON);
+    }
+
+    fn trajectory(sim: &mut Simulation) -> Vec<(f64, f64)> {
+        let mut points = Vec::new();
+        while sim.step() {
+            points.extend(sim.particles().iter().map(Particle::position));
+        }
+        points
+    }
+
+    #[test]
+    fn same_seed_reproduces_trajectories() {
+        let opts = ConfettiOptions {
+            seed: Some(1234),
+            ticks: 50,
+            ..Default::default()
+        };
+        let mut a = Simulation::new(half);
+        let mut b = Simulation::new(|| 0.25);
+        a.spawn(&opts, 640.0, 480.0);
+        b.spawn(&opts, 640.0, 480.0);
+        let (ta, tb) = (trajectory(&mut a), trajectory(&mut b));
+        assert!(!ta.is_empty());
+        assert!(ta
+            .iter()
+            .zip(&tb)
+            .all(|(p, q)| p.0.to_bits() == q.0.to_bits() && p.1.to_bits() == q.1.to_bits()));
+    }
+
+    #[test]
+    fn different_seeds_diverge() {
+        let mut a = Simulation::new(half);
+        let mut b = Simulation::new(half);
+        a.spawn(
+            &ConfettiOptions {
+                seed: Some(1),
+                ..opts()
+            },
+            100.0,
+            100.0,
+        );
+        b.spawn(
+            &ConfettiOptions {
+                seed: Some(2),
+                ..opts()
+            },
+            100.0,
+            100.0,
+        );
+        assert_ne!(trajectory(&mut a), trajectory(&mut b));
+    }
+
+    #[test]
+    fn lifetime_is_measured_in_real_time() {
+        let mut sim = Simulation::new(half);
+        sim.spawn(&opts(), 100.0, 100.0);
+        assert!(sim.advance_to(0.0));
+        assert!(sim.advance_to(1.0 / 144.0));
+        assert!(!sim.advance_to(0.1));
+    }
+
+    #[test]
+    fn cancelling_everything_restarts_the_clock() {
+        let seeded = ConfettiOptions {
+            ticks: 100,
+            seed: Some(1),
+            ..opts()
+        };
+        let mut sim = Simulation::new(half);
+        let first = sim.spawn(&seeded, 100.0, 100.0);
+        sim.advance_to(1.0);
+        sim.cancel(first);
+        sim.spawn(&seeded, 100.0, 100.0);
+        sim.advance_to(11.0);
+
+        let mut fresh = Simulation::new(half);
+        fresh.spawn(&seeded, 100.0, 100.0);
+        fresh.advance_to(11.0);
+        let positions = |sim: &Simulation| -> Vec<_> {
+            sim.particles().iter().map(Particle::position).collect()
+        };
+        assert_eq!(positions(&sim), positions(&fresh));
+    }
+
+    #[test]
+    fn frame_interpolates_between_ticks() {
+        let mut sim = Simulation::new(half);
+        let opts = ConfettiOptions {
+            start_velocity: 0.0,
+            ticks: 10,
+            ..opts()
+        };
+        sim.spawn(&opts, 100.0, 100.0);
+        sim.advance_to(0.0);
+        sim.advance_to(TICK_SECONDS * 1.5);
+        let y = sim.frame()[0].position().1;
+        assert!((y - 54.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn gradient_colors_particles_over_lifetime() {
+        let mut sim = Simulation::new(half);
+        let opts = ConfettiOptions {
+            ticks: 2,
+            gradient: Some(Gradient::even([Color::YELLOW, Color::RED])),
+            ..opts()
+        };
+        sim.spawn(&opts, 100.0, 100.0);
+        assert_eq!(sim.particles()[0].color(), Color::YELLOW);
+        sim.step();
+        assert_eq!(sim.particles()[0].color(), Color::new(255, 128, 0));
+    }
+
+    #[test]
+    fn gradient_jitter_varies_start_colors() {
+        let mut sim = Simulation::new(half);
+        let opts = ConfettiOptions {
+            gradient: Some(Gradient::even([Color::YELLOW, Color::RED]).with_jitter(1.0)),
+            seed: Some(3),
+            ..opts()
+        };
+        sim.spawn(&opts, 100.0, 100.0);
+        let first = sim.particles()[0].color();
+        assert!(sim.particles().iter().any(|p| p.color() != first));
+    }
+
+    #[test]
+    fn text_shapes_scale_their_box() {
+        let area = |shape: Shape| {
+            let mut sim = Simulation::new(half);
+            let opts = ConfettiOptions {
+                shapes: vec![shape],
+                seed: Some(8),
+                ..opts()
+            };
+            sim.spawn(&opts, 100.0, 100.0);
+            sim.step();
+            let [a, b, c, d, _, _] = sim.particles()[0].unit_transform();
+            (a * d - b * c).abs()
+        };
+        let big = Shape::Text {
+            text: "🎉".into(),
+            font: "serif".into(),
+            scalar: 2.0,
+        };
+        assert!((area(big) / area(Shape::Square) - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn polygon_shapes_flip_with_tilt() {
+        let signed_area = |points: &[(f64, f64)]| {
+            let n = points.len();
+            (0..n)
+                .map(|i| {
+                    let (a, b) = (points[i], points[(i + 1) % n]);
+                    a.0 * b.1 - b.0 * a.1
+                })
+                .sum::<f64>()
+        };
+        for shape in [Shape::Star, Shape::Heart, Shape::Crescent] {
+            let mut sim = Simulation::new(half);
+            let opts = ConfettiOptions {
+                shapes: vec![shape],
+                ticks: 100,
+                seed: Some(6),
+                ..opts()
+            };
+            sim.spawn(&opts, 100.0, 100.0);
+            let mut signs = Vec::new();
+            for _ in 0..99 {
+                sim.step();
+                signs.push(signed_area(&sim.particles()[0].outline()) > 0.0);
+            }
+            assert!(signs.contains(&true) && signs.contains(&false));
+        }
+    }
+
+    #[test]
+    fn ribbons_trail_their_particle() {
+        let mut sim = Simulation::new(half);
+        let opts = ConfettiOptions {
+            shapes: vec![Shape::Ribbon],
+            seed: Some(2),
+            ..opts()
+        };
+        sim.spawn(&opts, 100.0, 100.0);
+        sim.step();
+        let p = &sim.particles()[0];
+        let sections = p.ribbon_sections().expect("ribbon");
+        assert_eq!(sections.len(), RIBBON_POINTS);
+        assert_eq!(p.outline().len(), 2 * RIBBON_POINTS);
+        for _ in 0..5 {
+            sim.step();
+            for p in sim.particles() {
+                let [l, r] = p.ribbon_sections().expect("ribbon")[0];
+                let head = (f64::midpoint(l.0, r.0), f64::midpoint(l.1, r.1));
+                assert!((head.0 - p.position().0).abs() < 1e-9);
+                assert!((head.1 - p.position().1).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn bounds_enclose_the_outline() {
+        let mut sim = Simulation::new(half);
+        let opts = ConfettiOptions {
+            shapes: vec![Shape::Star],
+            seed: Some(4),
+            ..opts()
+        };
+        sim.spawn(&opts, 100.0, 100.0);
+        sim.step();
+        let p = &sim.particles()[0];
+        let (x0, y0, x1, y1) = p.bounds();
+        assert!(x0 < x1 && y0 < y1);
+        assert!(p
+            .outline()
+            .iter()
+            .all(|&(x, y)| (x0..=x1).contains(&x) && (y0..=y1).contains(&y)));
+    }
+
+    #[test]
+    fn opacity_fades_over_lifetime() {
+        let mut sim = Simulation::new(half);
+        sim.spawn(&opts(), 100.0, 100.0);
+        assert!((sim.particles()[0].opacity() - 1.0).abs() < f64::EPSILON);
+        sim.step();
+        assert!((sim.particles()[0].opacity() - 2.0 / 3.0).abs() < 1e-12);
+    }
+}
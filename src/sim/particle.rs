@@ -0,0 +1,342 @@
+//! Backend-free particle physics (canvas-confetti model)
+
+use super::geometry;
+use super::pose::Pose;
+use super::ribbon::Ribbon;
+use super::rng::Rng;
+use super::BurstId;
+use crate::types::{Color, ConfettiOptions, Gradient, Shape};
+use libm::{cos, sin};
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+const OVAL_SCALAR: f64 = 0.6;
+
+/// A single confetti particle.
+///
+/// Holds only simulation state; renderers read its geometry through the
+/// accessor methods and never mutate it.
+#[derive(Clone, Debug)]
+pub struct Particle {
+    id: u64,
+    burst: BurstId,
+    pose: Pose,
+    prev: Pose,
+    wobble_speed: f64,
+    velocity: f64,
+    angle_2d: f64,
+    tilt_angle: f64,
+    color: Color,
+    gradient: Option<Arc<Gradient>>,
+    gradient_start: f64,
+    shape: Shape,
+    ribbon: Option<Ribbon>,
+    tick: u32,
+    total_ticks: u32,
+    decay: f64,
+    drift: f64,
+    gravity: f64,
+    scalar: f64,
+    flat: bool,
+    rng: Rng,
+}
+
+impl Particle {
+    #[must_use]
+    pub fn new(
+        opts: &ConfettiOptions,
+        start: (f64, f64),
+        color: Color,
+        shape: Shape,
+        mut rng: Rng,
+    ) -> Self {
+        let rad_angle = opts.angle * (PI / 180.0);
+        let rad_spread = opts.spread * (PI / 180.0);
+        let pose = Pose {
+            x: start.0,
+            y: start.1,
+            wobble: rng.next_f64() * 10.0,
+            ..Pose::default()
+        };
+        let wobble_speed = f64::min(0.11, rng.next_f64() * 0.1 + 0.05);
+        let velocity = (opts.start_velocity * 0.5) + (rng.next_f64() * opts.start_velocity);
+        let angle_2d = -rad_angle + ((0.5 * rad_spread) - (rng.next_f64() * rad_spread));
+        let ribbon = (shape == Shape::Ribbon).then(|| Ribbon::new(start, angle_2d, opts.scalar));
+        let mut particle = Self {
+            id: 0,
+            burst: BurstId::default(),
+            pose,
+            prev: pose,
+            wobble_speed,
+            velocity,
+            angle_2d,
+            tilt_angle: (rng.next_f64() * 0.5 + 0.25) * PI,
+            color,
+            gradient: None,
+            gradient_start: 0.0,
+            shape,
+            ribbon,
+            tick: 0,
+            total_ticks: opts.ticks,
+            decay: opts.decay,
+            drift: opts.drift,
+            gravity: opts.gravity * 3.0,
+            scalar: opts.scalar,
+            flat: opts.flat,
+            rng,
+        };
+        particle.pose.random = particle.rng.next_f64() + 2.0;
+        particle.settle();
+        particle.prev = particle.pose;
+        particle
+    }
+
+    /// Tag the particle with an identifier unique within its simulation.
+    #[must_use]
+    pub(crate) fn with_id(mut self, id: u64) -> Self {
+        self.id = id;
+        self
+    }
+
+    /// Tag the particle with the burst that spawned it.
+    #[must_use]
+    pub(crate) fn with_burst(mut self, burst: BurstId) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    /// Color the particle along `gradient` over its lifetime instead of with
+    /// its fixed color, starting at a random point within the jitter.
+    #[must_use]
+    pub fn with_gradient(mut self, gradient: Arc<Gradient>) -> Self {
+        self.gradient_start = self.rng.next_f64() * gradient.jitter();
+        self.gradient = Some(gradient);
+        self
+    }
+
+    /// Advance one tick. Returns `false` once the particle has expired.
+    pub fn update(&mut self) -> bool {
+        self.prev = self.pose;
+        self.pose.x += cos(self.angle_2d) * self.velocity + self.drift;
+        self.pose.y += sin(self.angle_2d) * self.velocity + self.gravity;
+        self.velocity *= self.decay;
+        if !self.flat {
+            self.pose.wobble += self.wobble_speed;
+            self.tilt_angle += 0.1;
+            self.pose.random = self.rng.next_f64() + 2.0;
+        }
+        self.settle();
+        if let Some(ribbon) = &mut self.ribbon {
+            ribbon.step((self.pose.x, self.pose.y), self.drift, self.gravity);
+        }
+        self.tick += 1;
+        self.tick < self.total_ticks
+    }
+
+    /// Copy of this particle posed `alpha` of the way from the previous tick
+    /// to the current one.
+    #[must_use]
+    pub fn interpolate(&self, alpha: f64) -> Self {
+        Self {
+            pose: self.prev.lerp(&self.pose, alpha),
+            ribbon: self.ribbon.as_ref().map(|r| r.interpolate(alpha)),
+            ..self.clone()
+        }
+    }
+
+    /// Recompute the wobble and tilt geometry from the current angles.
+    fn settle(&mut self) {
+        let reach = 10.0 * self.scalar;
+        let pose = &mut self.pose;
+        if self.flat {
+            pose.wobble = 0.0;
+            pose.wobble_x = pose.x + reach;
+            pose.wobble_y = pose.y + reach;
+            pose.tilt_sin = 0.0;
+            pose.tilt_cos = 0.0;
+            pose.random = 1.0;
+        } else {
+            pose.wobble_x = pose.x + reach * cos(pose.wobble);
+            pose.wobble_y = pose.y + reach * sin(pose.wobble);
+            pose.tilt_sin = sin(self.tilt_angle);
+            pose.tilt_cos = cos(self.tilt_angle);
+        }
+    }
+
+    /// Identifier that stays the same for the particle's whole lifetime.
+    #[must_use]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// The burst this particle was spawned in.
+    #[must_use]
+    pub fn burst(&self) -> BurstId {
+        self.burst
+    }
+
+    #[must_use]
+    pub fn position(&self) -> (f64, f64) {
+        (self.pose.x, self.pose.y)
+    }
+
+    /// Current color: the fixed color, or the gradient sampled at the
+    /// particle's age.
+    #[must_use]
+    pub fn color(&self) -> Color {
+        self.gradient.as_ref().map_or(self.color, |g| {
+            let start = self.gradient_start;
+            g.sample(start + self.progress() * (1.0 - start))
+        })
+    }
+
+    #[must_use]
+    pub fn shape(&self) -> &Shape {
+        &self.shape
+    }
+
+    #[must_use]
+    pub fn scalar(&self) -> f64 {
+        self.scalar
+    }
+
+    /// Current wobble phase, used by renderers for rotation.
+    #[must_use]
+    pub fn wobble(&self) -> f64 {
+        self.pose.wobble
+    }
+
+    /// Fraction of the lifetime elapsed, from 0.0 at spawn to 1.0 at expiry.
+    #[must_use]
+    pub fn progress(&self) -> f64 {
+        f64::from(self.tick) / f64::from(self.total_ticks)
+    }
+
+    /// Remaining opacity, fading linearly from 1.0 to 0.0 over the lifetime.
+    #[must_use]
+    pub fn opacity(&self) -> f64 {
+        1.0 - self.progress()
+    }
+
+    /// The four tilted corners of the particle's quad, in drawing order.
+    #[must_use]
+    pub fn corners(&self) -> [(f64, f64); 4] {
+        let p = &self.pose;
+        let (x1, y1) = self.tilt_offset(p.x, p.y);
+        let (x2, y2) = self.tilt_offset(p.wobble_x, p.wobble_y);
+        [(p.x, p.y), (p.wobble_x, y1), (x2, y2), (x1, p.wobble_y)]
+    }
+
+    /// Radii and rotation of the ellipse drawn for circular particles.
+    #[must_use]
+    pub fn ellipse(&self) -> (f64, f64, f64) {
+        let p = &self.pose;
+        let rx = (p.wobble_x - p.x).abs() * OVAL_SCALAR;
+        let ry = (p.wobble_y - p.y).abs() * OVAL_SCALAR;
+        (rx.max(0.1), ry.max(0.1), PI / 10.0 * p.wobble)
+    }
+
+    /// Affine map from the unit box (`-0.5..=0.5`) onto the particle's
+    /// tilted quad, as the `a, b, c, d, e, f` arguments of a canvas
+    /// `transform()` call. Text shapes scale the quad by their own `scalar`.
+    #[must_use]
+    pub fn unit_transform(&self) -> [f64; 6] {
+        let [c0, c1, c2, c3] = self.corners();
+        let centre = ((c0.0 + c2.0) * 0.5, (c0.1 + c2.1) * 0.5);
+        let k = match &self.shape {
+            Shape::Text { scalar, .. } => *scalar,
+            _ => 1.0,
+        };
+        [
+            (c1.0 - c0.0) * k,
+            (c1.1 - c0.1) * k,
+            (c3.0 - c0.0) * k,
+            (c3.1 - c0.1) * k,
+            centre.0,
+            centre.1,
+        ]
+    }
+
+    /// Closed polygon approximating the drawn shape, in pixel space.
+    ///
+    /// Squares, circles and text are star-shaped around
+    /// [`Particle::position`], so they can be triangulated as a fan from that
+    /// point. Built-in polygons (see [`Shape::unit_outline`]) may be concave
+    /// and ribbons are strips; triangulate those from their unit outline or
+    /// [`Particle::ribbon_sections`] instead. Path and text shapes return
+    /// their bounding quad and images their own rectangle; use
+    /// [`Particle::outlines`] for exact path geometry.
+    #[must_use]
+    pub fn outline(&self) -> Vec<(f64, f64)> {
+        if let Some(unit) = self.shape.unit_outline() {
+            let m = self.unit_transform();
+            return unit.into_iter().map(|p| geometry::apply(&m, p)).collect();
+        }
+        match &self.shape {
+            Shape::Text { .. } => self.unit_rect(0.5, 0.5),
+            Shape::Image(image) => {
+                let (hw, hh) = image.half_extents();
+                self.unit_rect(hw, hh)
+            }
+            Shape::Circle => {
+                let (rx, ry, rotation) = self.ellipse();
+                geometry::ellipse(self.position(), rx, ry, rotation)
+            }
+            Shape::Ribbon => {
+                let sections = self.ribbon_sections().unwrap_or_default();
+                let left = sections.iter().map(|[l, _]| *l);
+                let right = sections.iter().rev().map(|[_, r]| *r);
+                left.chain(right).collect()
+            }
+            _ => self.corners().to_vec(),
+        }
+    }
+
+    /// Left and right edge at each point of a ribbon, from head to tail,
+    /// twisting with the wobble. `None` for other shapes.
+    #[must_use]
+    pub fn ribbon_sections(&self) -> Option<Vec<[(f64, f64); 2]>> {
+        let phase = (!self.flat).then_some(self.pose.wobble);
+        self.ribbon.as_ref().map(|r| r.sections(phase))
+    }
+
+    /// Axis-aligned box around the drawn shape, as
+    /// `(min_x, min_y, max_x, max_y)` in pixel space.
+    #[must_use]
+    pub fn bounds(&self) -> (f64, f64, f64, f64) {
+        let (x, y) = self.position();
+        self.outlines()
+            .iter()
+            .flatten()
+            .fold((x, y, x, y), |(x0, y0, x1, y1), &(px, py)| {
+                (x0.min(px), y0.min(py), x1.max(px), y1.max(py))
+            })
+    }
+
+    /// Every closed polyline of the drawn shape in pixel space, to be
+    /// filled together with the non-zero winding rule.
+    #[must_use]
+    pub fn outlines(&self) -> Vec<Vec<(f64, f64)>> {
+        let Shape::Path(path) = &self.shape else {
+            return vec![self.outline()];
+        };
+        let m = self.unit_transform();
+        path.subpaths()
+            .iter()
+            .map(|sub| sub.iter().map(|&p| geometry::apply(&m, p)).collect())
+            .collect()
+    }
+
+    /// The unit-box rectangle `±hw, ±hh` mapped onto the particle.
+    fn unit_rect(&self, hw: f64, hh: f64) -> Vec<(f64, f64)> {
+        let m = self.unit_transform();
+        [(-hw, -hh), (hw, -hh), (hw, hh), (-hw, hh)]
+            .map(|p| geometry::apply(&m, p))
+            .to_vec()
+    }
+
+    fn tilt_offset(&self, x: f64, y: f64) -> (f64, f64) {
+        let p = &self.pose;
+        (x + p.random * p.tilt_cos, y + p.random * p.tilt_sin)
+    }
+}
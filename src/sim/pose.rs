@@ -0,0 +1,33 @@
+//! Drawable state of a particle at one tick.
+
+/// Everything a renderer needs to place a particle, separated from the
+/// dynamics so two consecutive ticks can be blended.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Pose {
+    pub x: f64,
+    pub y: f64,
+    pub wobble: f64,
+    pub wobble_x: f64,
+    pub wobble_y: f64,
+    pub tilt_sin: f64,
+    pub tilt_cos: f64,
+    pub random: f64,
+}
+
+impl Pose {
+    /// Linear blend from `self` (at `t = 0`) towards `to` (at `t = 1`).
+    #[must_use]
+    pub fn lerp(&self, to: &Self, t: f64) -> Self {
+        let mix = |a: f64, b: f64| a + (b - a) * t;
+        Self {
+            x: mix(self.x, to.x),
+            y: mix(self.y, to.y),
+            wobble: mix(self.wobble, to.wobble),
+            wobble_x: mix(self.wobble_x, to.wobble_x),
+            wobble_y: mix(self.wobble_y, to.wobble_y),
+            tilt_sin: mix(self.tilt_sin, to.tilt_sin),
+            tilt_cos: mix(self.tilt_cos, to.tilt_cos),
+            random: mix(self.random, to.random),
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Chain of trailing points behind a streamer particle.
+
+use libm::{cos, sin};
+
+/// Points in the chain, including the head.
+pub const RIBBON_POINTS: usize = 12;
+
+/// Rest length of each link, before `scalar`.
+const SEGMENT: f64 = 3.0;
+
+/// Half the ribbon's width, before `scalar`.
+const HALF_WIDTH: f64 = 2.5;
+
+/// Velocity kept by trailing points each tick.
+const DAMPING: f64 = 0.9;
+
+/// Share of the head's gravity felt by trailing points, so the tail hangs
+/// without outrunning the head.
+const SAG: f64 = 0.1;
+
+/// Phase shift of the twist between neighbouring points, in radians.
+const TWIST: f64 = 0.6;
+
+/// Verlet chain that follows the particle's position with fixed-length
+/// links, so the tail swings and curls behind the head.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ribbon {
+    points: Vec<(f64, f64)>,
+    prev: Vec<(f64, f64)>,
+    segment: f64,
+    half_width: f64,
+}
+
+impl Ribbon {
+    /// A straight ribbon at rest, trailing from `head` away from `heading`
+    /// (radians, the direction of travel).
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(head: (f64, f64), heading: f64, scalar: f64) -> Self {
+        let segment = SEGMENT * scalar;
+        let back = (-cos(heading) * segment, -sin(heading) * segment);
+        let points: Vec<(f64, f64)> = (0..RIBBON_POINTS)
+            .map(|i| {
+                let i = i as f64;
+                (head.0 + back.0 * i, head.1 + back.1 * i)
+            })
+            .collect();
+        Self {
+            prev: points.clone(),
+            points,
+            segment,
+            half_width: HALF_WIDTH * scalar,
+        }
+    }
+
+    /// Advance one tick with the head moved to `head`, pulling each trailing
+    /// point by `drift` and `gravity` before restoring the link lengths.
+    pub fn step(&mut self, head: (f64, f64), drift: f64, gravity: f64) {
+        self.prev.clone_from(&self.points);
+        self.points[0] = head;
+        for i in 1..self.points.len() {
+            let (x, y) = self.points[i];
+            let (px, py) = self.prev[i];
+            let moved = (
+                x + (x - px) * DAMPING + drift * SAG,
+                y + (y - py) * DAMPING + gravity * SAG,
+            );
+            self.points[i] = self.constrain(self.points[i - 1], moved);
+        }
+    }
+
+    /// `point` pulled or pushed along the link to sit one segment from
+    /// `parent`.
+    fn constrain(&self, parent: (f64, f64), point: (f64, f64)) -> (f64, f64) {
+        let (dx, dy) = (point.0 - parent.0, point.1 - parent.1);
+        let len = dx.hypot(dy);
+        if len < f64::EPSILON {
+            return (parent.0, parent.1 + self.segment);
+        }
+        let k = self.segment / len;
+        (parent.0 + dx * k, parent.1 + dy * k)
+    }
+
+    /// Copy posed `alpha` of the way from the previous tick to this one.
+    #[must_use]
+    pub fn interpolate(&self, alpha: f64) -> Self {
+        let points = self
+            .prev
+            .iter()
+            .zip(&self.points)
+            .map(|(a, b)| (a.0 + (b.0 - a.0) * alpha, a.1 + (b.1 - a.1) * alpha))
+            .collect();
+        Self {
+            points,
+            ..self.clone()
+        }
+    }
+
+    /// Left and right edge at each point. The width follows
+    /// `cos(phase + i * TWIST)`, so the strip narrows and crosses over
+    /// itself as if twisting; a flat ribbon passes `None` and keeps its full
+    /// width.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn sections(&self, phase: Option<f64>) -> Vec<[(f64, f64); 2]> {
+        let n = self.points.len();
+        (0..n)
+            .map(|i| {
+                let prev = self.points[i.saturating_sub(1)];
+                let next = self.points[(i + 1).min(n - 1)];
+                let (tx, ty) = (next.0 - prev.0, next.1 - prev.1);
+                let len = tx.hypot(ty).max(f64::EPSILON);
+                let twist = phase.map_or(1.0, |phase| cos(phase + i as f64 * TWIST));
+                let width = self.half_width * twist;
+                let (nx, ny) = (-ty / len * width, tx / len * width);
+                let (x, y) = self.points[i];
+                [(x + nx, y + ny), (x - nx, y - ny)]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link_lengths(ribbon: &Ribbon) -> Vec<f64> {
+        ribbon
+            .points
+            .windows(2)
+            .map(|w| (w[1].0 - w[0].0).hypot(w[1].1 - w[0].1))
+            .collect()
+    }
+
+    #[test]
+    fn links_keep_their_length() {
+        let mut ribbon = Ribbon::new((0.0, 0.0), 0.0, 1.0);
+        for i in 0..50 {
+            let t = f64::from(i);
+            ribbon.step((t * 2.0, t * t * 0.1), 0.0, 3.0);
+        }
+        assert!(link_lengths(&ribbon)
+            .iter()
+            .all(|l| (l - SEGMENT).abs() < 1e-9));
+    }
+
+    #[test]
+    fn tail_trails_the_head() {
+        let mut ribbon = Ribbon::new((0.0, 0.0), 0.0, 1.0);
+        assert!(ribbon.points[RIBBON_POINTS - 1].0 < 0.0);
+        for i in 1..=20 {
+            ribbon.step((0.0, f64::from(i) * -5.0), 0.0, 0.0);
+        }
+        // Rising head: the tail hangs below it.
+        let tail = ribbon.points[RIBBON_POINTS - 1];
+        assert!(tail.1 > ribbon.points[0].1);
+    }
+
+    #[test]
+    fn flat_sections_have_full_width() {
+        let ribbon = Ribbon::new((0.0, 0.0), 0.0, 2.0);
+        for [l, r] in ribbon.sections(None) {
+            assert!(((l.1 - r.1).abs() - 4.0 * HALF_WIDTH).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! Confetti driven by the host's own render loop.
+//!
+//! [`ConfettiSystem`] owns particles but no canvas, window or animation
+//! frame callback: the host calls [`ConfettiSystem::step`] with its own frame
+//! time and draws the particles wherever they belong in its z-order, with
+//! [`ConfettiSystem::draw`] on a 2D canvas or `desktop::Renderer` in a wgpu
+//! render pass.
+
+use crate::handle::{BurstHandle, BurstTracker};
+use crate::presets::Preset;
+use crate::sim::{Particle, Simulation};
+use crate::types::ConfettiOptions;
+
+#[cfg(feature = "web")]
+use wasm_bindgen::prelude::*;
+#[cfg(feature = "web")]
+use web_sys::CanvasRenderingContext2d;
+
+/// Particles on a `width` x `height` surface, stepped and drawn on demand.
+#[cfg_attr(feature = "web", wasm_bindgen)]
+pub struct ConfettiSystem {
+    sim: Simulation,
+    tracker: BurstTracker,
+    width: f64,
+    height: f64,
+}
+
+impl ConfettiSystem {
+    /// An empty system whose bursts are placed on a `width` x `height`
+    /// surface, in the same units the host draws in.
+    #[must_use]
+    pub fn new(width: f64, height: f64) -> Self {
+        #[cfg(feature = "web")]
+        let entropy = crate::animation::random;
+        #[cfg(not(feature = "web"))]
+        let entropy = crate::sim::std_entropy;
+        Self {
+            sim: Simulation::new(entropy),
+            tracker: BurstTracker::new(),
+            width,
+            height,
+        }
+    }
+
+    /// Place later bursts on a surface of the new size. Particles already in
+    /// flight keep their positions.
+    pub fn set_size(&mut self, width: f64, height: f64) {
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Surface size as `(width, height)`.
+    #[must_use]
+    pub fn size(&self) -> (f64, f64) {
+        (self.width, self.height)
+    }
+
+    /// Add a burst. Its handle resolves during the [`step`](Self::step) that
+    /// removes its last particle.
+    pub fn spawn(&mut self, opts: &ConfettiOptions) -> BurstHandle {
+        self.spawn_preset(&Preset::from(opts.clone()))
+    }
+
+    /// Add every burst of a preset, with one handle for all of them.
+    pub fn spawn_preset(&mut self, preset: &Preset) -> BurstHandle {
+        let ids = preset
+            .bursts()
+            .iter()
+            .filter(|opts| !skipped(opts))
+            .map(|opts| self.sim.spawn(opts, self.width, self.height))
+            .collect();
+        self.tracker.handle(ids)
+    }
+
+    /// Advance by `dt` seconds of frame time, resolving handles of finished
+    /// or cancelled bursts.
+    ///
+    /// Returns `true` while any particle is still alive.
+    pub fn step(&mut self, dt: f64) -> bool {
+        self.sim.advance(dt);
+        self.tracker.sync(&mut self.sim);
+        !self.sim.is_empty()
+    }
+
+    /// Particles as they should be drawn this frame.
+    #[must_use]
+    pub fn frame(&self) -> Vec<Particle> {
+        self.sim.frame()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sim.is_empty()
+    }
+
+    /// Remove every particle, resolving all pending handles.
+    pub fn reset(&mut self) {
+        self.sim.clear();
+        self.tracker = BurstTracker::new();
+    }
+
+    /// Draw the current frame onto `ctx`, on top of whatever the host has
+    /// drawn so far. Nothing is cleared.
+    #[cfg(feature = "web")]
+    pub fn draw(&self, ctx: &CanvasRenderingContext2d) {
+        crate::canvas::render_frame(&self.sim, ctx);
+    }
+}
+
+/// Whether `opts` opts out of running for users who prefer reduced motion.
+#[cfg(feature = "web")]
+fn skipped(opts: &ConfettiOptions) -> bool {
+    opts.disable_for_reduced_motion && crate::animation::prefers_reduced_motion()
+}
+
+#[cfg(not(feature = "web"))]
+fn skipped(_: &ConfettiOptions) -> bool {
+    false
+}
+
+#[cfg(feature = "web")]
+#[wasm_bindgen]
+impl ConfettiSystem {
+    /// `new ConfettiSystem(width, height)`.
+    #[wasm_bindgen(constructor)]
+    #[must_use]
+    pub fn new_js(width: f64, height: f64) -> Self {
+        Self::new(width, height)
+    }
+
+    /// Add the default burst.
+    #[wasm_bindgen(js_name = spawn)]
+    pub fn spawn_js(&mut self) -> BurstHandle {
+        self.spawn(&ConfettiOptions::default())
+    }
+
+    /// Add the preset registered or built in under `name`, or return
+    /// `undefined` if there is none.
+    #[wasm_bindgen(js_name = spawnPreset)]
+    pub fn spawn_preset_js(&mut self, name: &str) -> Option<BurstHandle> {
+        crate::presets::preset(name).map(|p| self.spawn_preset(&p))
+    }
+
+    /// Advance by `dt` seconds; `false` once nothing is left.
+    #[wasm_bindgen(js_name = step)]
+    pub fn step_js(&mut self, dt: f64) -> bool {
+        self.step(dt)
+    }
+
+    #[wasm_bindgen(js_name = draw)]
+    pub fn draw_js(&self, ctx: &CanvasRenderingContext2d) {
+        self.draw(ctx);
+    }
+
+    #[wasm_bindgen(js_name = setSize)]
+    pub fn set_size_js(&mut self, width: f64, height: f64) {
+        self.set_size(width, height);
+    }
+
+    #[wasm_bindgen(js_name = reset)]
+    pub fn reset_js(&mut self) {
+        self.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn burst() -> ConfettiOptions {
+        ConfettiOptions {
+            particle_count: 20,
+            ticks: 30,
+            seed: Some(9),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn step_runs_until_the_handle_resolves() {
+        let mut system = ConfettiSystem::new(400.0, 300.0);
+        let handle = system.spawn(&burst());
+        assert!(!handle.is_finished());
+        let mut frames = 0;
+        while system.step(1.0 / 60.0) {
+            frames += 1;
+            assert!(frames < 1000, "burst never expired");
+        }
+        assert!(handle.is_finished());
+        assert!(system.frame().is_empty());
+    }
+
+    #[test]
+    fn bursts_start_at_the_origin_within_the_size() {
+        let mut system = ConfettiSystem::new(400.0, 300.0);
+        system.set_size(200.0, 100.0);
+        system.spawn(&burst());
+        for p in system.frame() {
+            let (x, y) = p.position();
+            assert!((x - 100.0).abs() < 1e-9 && (y - 50.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn cancelled_bursts_leave_on_the_next_step() {
+        let mut system = ConfettiSystem::new(400.0, 300.0);
+        let kept = system.spawn(&burst());
+        system.spawn(&burst()).cancel();
+        system.step(0.0);
+        assert_eq!(system.frame().len(), 20);
+        system.reset();
+        assert!(kept.is_finished() && system.is_empty());
+    }
+}
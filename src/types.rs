@@ -1,47 +1,7 @@
 //! Public types for confetti configuration.
 
-/// RGB color representation
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub struct Color {
-    pub r: u8,
-    pub g: u8,
-    pub b: u8,
-}
-
-impl Color {
-    pub const RED: Self = Self::new(255, 0, 0);
-    pub const GREEN: Self = Self::new(0, 255, 0);
-    pub const BLUE: Self = Self::new(0, 0, 255);
-    pub const YELLOW: Self = Self::new(255, 255, 0);
-    pub const CYAN: Self = Self::new(0, 255, 255);
-    pub const MAGENTA: Self = Self::new(255, 0, 255);
-    pub const WHITE: Self = Self::new(255, 255, 255);
-
-    #[must_use]
-    pub const fn new(r: u8, g: u8, b: u8) -> Self {
-        Self { r, g, b }
-    }
-
-    #[must_use]
-    pub fn from_hex(hex: &str) -> Self {
-        let hex = hex.trim_start_matches('#');
-        let hex = if hex.len() == 3 {
-            let chars: Vec<char> = hex.chars().collect();
-            format!(
-                "{}{}{}{}{}{}",
-                chars[0], chars[0], chars[1], chars[1], chars[2], chars[2]
-            )
-        } else {
-            hex.to_string()
-        };
-
-        let r = u8::from_str_radix(&hex[0..2], 16).unwrap_or(0);
-        let g = u8::from_str_radix(&hex[2..4], 16).unwrap_or(0);
-        let b = u8::from_str_radix(&hex[4..6], 16).unwrap_or(0);
-
-        Self { r, g, b }
-    }
-}
+pub use crate::color::{Color, Gradient, GradientStop, ParseColorError};
+pub use crate::shape::{ImageError, ImageShape, PathError, PathShape, Shape, DEFAULT_FONT};
 
 /// Default confetti color palette
 #[must_use]
@@ -57,17 +17,13 @@ pub fn default_colors() -> Vec<Color> {
     ]
 }
 
-/// Shape of confetti particles
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
-pub enum Shape {
-    #[default]
-    Square,
-    Circle,
-    Star,
-}
-
 /// Origin point for confetti emission (0.0 to 1.0, relative to canvas)
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Origin {
     pub x: f64,
     pub y: f64,
@@ -81,6 +37,11 @@ impl Default for Origin {
 
 /// Configuration options for confetti animation
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ConfettiOptions {
     pub particle_count: u32,
     pub angle: f64,
@@ -97,6 +58,12 @@ pub struct ConfettiOptions {
     pub z_index: i32,
     pub flat: bool,
     pub disable_for_reduced_motion: bool,
+    /// Seed for the burst's random numbers. The same seed and options yield
+    /// identical trajectories on every platform; `None` picks a fresh seed.
+    pub seed: Option<u64>,
+    /// Colors each particle passes through over its lifetime. When set it
+    /// replaces `colors`; opacity still fades out as usual.
+    pub gradient: Option<Gradient>,
 }
 
 impl Default for ConfettiOptions {
@@ -117,6 +84,8 @@ impl Default for ConfettiOptions {
             z_index: 100,
             flat: false,
             disable_for_reduced_motion: false,
+            seed: None,
+            gradient: None,
         }
     }
 }
@@ -178,6 +147,8 @@ mod tests {
         assert_eq!(opts.ticks, 200);
         assert!(!opts.shapes.is_empty());
         assert!(!opts.colors.is_empty());
+        assert!(opts.seed.is_none());
+        assert!(opts.gradient.is_none());
     }
 
     #[test]
@@ -0,0 +1,225 @@
+//! Canvas sizing for high-density displays, and watching for size changes.
+//!
+//! Particles are simulated in CSS pixels, so they look the same size on
+//! every display. The canvas's drawing buffer is scaled up by
+//! `devicePixelRatio` so they stay sharp, and the context is scaled to match
+//! while drawing.
+
+use std::cell::Cell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ResizeObserver};
+
+/// A canvas's drawing area in CSS pixels, and drawing-buffer pixels per CSS
+/// pixel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub width: f64,
+    pub height: f64,
+    pub scale: f64,
+}
+
+impl Viewport {
+    /// The drawing buffer of `canvas` as it is, one unit per pixel.
+    pub fn of(canvas: &HtmlCanvasElement) -> Self {
+        Self {
+            width: f64::from(canvas.width()),
+            height: f64::from(canvas.height()),
+            scale: 1.0,
+        }
+    }
+
+    /// Drawing-buffer size for this viewport, in whole pixels.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn backing_size(&self) -> (u32, u32) {
+        (
+            (self.width * self.scale).round() as u32,
+            (self.height * self.scale).round() as u32,
+        )
+    }
+
+    /// Resize the drawing buffer of `canvas` to match, if it differs.
+    pub fn apply(&self, canvas: &HtmlCanvasElement) {
+        let (width, height) = self.backing_size();
+        if canvas.width() != width {
+            canvas.set_width(width);
+        }
+        if canvas.height() != height {
+            canvas.set_height(height);
+        }
+    }
+
+    /// Scale `ctx` so drawing in CSS pixels covers the drawing buffer.
+    pub fn enter(&self, ctx: &CanvasRenderingContext2d) {
+        let _ = ctx.set_transform(self.scale, 0.0, 0.0, self.scale, 0.0, 0.0);
+    }
+
+    /// Undo [`enter`](Self::enter).
+    pub fn leave(ctx: &CanvasRenderingContext2d) {
+        let _ = ctx.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+    }
+}
+
+fn device_pixel_ratio() -> f64 {
+    web_sys::window()
+        .map(|w| w.device_pixel_ratio())
+        .filter(|ratio| ratio.is_finite() && *ratio > 0.0)
+        .unwrap_or(1.0)
+}
+
+/// The window's inner size and pixel ratio.
+pub fn window() -> Viewport {
+    let window = web_sys::window();
+    let inner = |size: Option<JsValue>, fallback| size.and_then(|v| v.as_f64()).unwrap_or(fallback);
+    Viewport {
+        width: inner(window.as_ref().and_then(|w| w.inner_width().ok()), 800.0),
+        height: inner(window.as_ref().and_then(|w| w.inner_height().ok()), 600.0),
+        scale: device_pixel_ratio(),
+    }
+}
+
+/// Size the fullscreen overlay to the window.
+pub fn fill_window(canvas: &HtmlCanvasElement) -> Viewport {
+    let viewport = window();
+    viewport.apply(canvas);
+    viewport
+}
+
+/// Size `canvas` to its on-page size, or return `None` while it takes up no
+/// space on the page.
+///
+/// The measured size is also set as the canvas's CSS size. Otherwise an
+/// unstyled canvas would be laid out at its new, larger drawing buffer, and
+/// grow again on every fit.
+pub fn fit(canvas: &HtmlCanvasElement) -> Option<Viewport> {
+    fit_at(canvas, device_pixel_ratio())
+}
+
+/// [`fit`] with `scale` drawing-buffer pixels per CSS pixel.
+fn fit_at(canvas: &HtmlCanvasElement, scale: f64) -> Option<Viewport> {
+    let rect = canvas.get_bounding_client_rect();
+    if rect.width() < 1.0 || rect.height() < 1.0 {
+        return None;
+    }
+    let viewport = Viewport {
+        width: rect.width(),
+        height: rect.height(),
+        scale,
+    };
+    let style = canvas.style();
+    let _ = style.set_property("width", &format!("{}px", viewport.width));
+    let _ = style.set_property("height", &format!("{}px", viewport.height));
+    viewport.apply(canvas);
+    Some(viewport)
+}
+
+/// Flags size changes of the window or of one canvas until dropped.
+pub struct ResizeWatch {
+    changed: Rc<Cell<bool>>,
+    source: Source,
+    callback: Closure<dyn FnMut()>,
+}
+
+enum Source {
+    Window(web_sys::Window),
+    Observer(ResizeObserver),
+}
+
+impl ResizeWatch {
+    /// Watch the window's `resize` event.
+    pub fn window() -> Option<Self> {
+        let window = web_sys::window()?;
+        let (changed, callback) = flag();
+        window
+            .add_event_listener_with_callback("resize", callback.as_ref().unchecked_ref())
+            .ok()?;
+        Some(Self {
+            changed,
+            source: Source::Window(window),
+            callback,
+        })
+    }
+
+    /// Watch `canvas` with a `ResizeObserver`.
+    pub fn element(canvas: &HtmlCanvasElement) -> Option<Self> {
+        let (changed, callback) = flag();
+        let observer = ResizeObserver::new(callback.as_ref().unchecked_ref()).ok()?;
+        observer.observe(canvas);
+        Some(Self {
+            changed,
+            source: Source::Observer(observer),
+            callback,
+        })
+    }
+
+    /// Whether the size changed since the last call.
+    pub fn take(&self) -> bool {
+        self.changed.replace(false)
+    }
+}
+
+impl Drop for ResizeWatch {
+    fn drop(&mut self) {
+        match &self.source {
+            Source::Window(window) => {
+                let _ = window.remove_event_listener_with_callback(
+                    "resize",
+                    self.callback.as_ref().unchecked_ref(),
+                );
+            }
+            Source::Observer(observer) => observer.disconnect(),
+        }
+    }
+}
+
+/// A flag and a callback that raises it.
+fn flag() -> (Rc<Cell<bool>>, Closure<dyn FnMut()>) {
+    let changed = Rc::new(Cell::new(false));
+    let raise = Rc::clone(&changed);
+    (changed, Closure::new(move || raise.set(true)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    #[test]
+    fn backing_size_scales_and_rounds() {
+        let viewport = Viewport {
+            width: 375.0,
+            height: 667.5,
+            scale: 3.0,
+        };
+        assert_eq!(viewport.backing_size(), (1125, 2003));
+        let fractional = Viewport {
+            scale: 1.25,
+            ..viewport
+        };
+        assert_eq!(fractional.backing_size(), (469, 834));
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    #[wasm_bindgen_test::wasm_bindgen_test]
+    fn fitting_twice_keeps_the_size() {
+        let document = web_sys::window()
+            .and_then(|w| w.document())
+            .expect("document");
+        let canvas: HtmlCanvasElement = document
+            .create_element("canvas")
+            .expect("canvas")
+            .unchecked_into();
+        document
+            .body()
+            .expect("body")
+            .append_child(&canvas)
+            .expect("append");
+        let first = fit_at(&canvas, 2.0).expect("laid out");
+        let second = fit_at(&canvas, 2.0).expect("laid out");
+        assert_eq!(first, second);
+        assert_eq!((canvas.width(), canvas.height()), first.backing_size());
+        canvas.remove();
+    }
+}
@@ -1,156 +1,193 @@
 //! WebAssembly implementation using HTML5 Canvas
 
-use crate::animation;
-use crate::particle::Particle;
-use crate::types::{Color, ConfettiOptions, Origin};
-use animation::{run_standalone_animation, start_animation, AnimationState, ANIMATION_STATE};
+use crate::error::GlitterbombError;
+use crate::handle::BurstHandle;
+use crate::instance::{warned, Confetti, ConfettiSettings};
+use crate::presets::{self, Preset};
+use crate::types::ConfettiOptions;
+use js_sys::{Function, Promise};
+use std::cell::RefCell;
 use wasm_bindgen::prelude::*;
 use web_sys::HtmlCanvasElement;
 
+thread_local! {
+    /// Instance behind the global functions, drawing on a fullscreen overlay.
+    static DEFAULT: Confetti = Confetti::create(None, ConfettiSettings::default());
+
+    /// Instances behind [`confetti_on_canvas`], one per canvas.
+    static CANVASES: RefCell<Vec<(HtmlCanvasElement, Confetti)>> = const { RefCell::new(Vec::new()) };
+}
+
 /// Fire confetti with the given options.
 ///
-/// # Panics
+/// The returned handle resolves once the burst is over and can cancel it.
+/// Nothing is drawn, apart from a console warning, if the page cannot host
+/// the overlay canvas; use [`try_confetti`] to handle that case yourself.
+pub fn confetti(opts: &ConfettiOptions) -> BurstHandle {
+    try_confetti(opts).unwrap_or_else(warned)
+}
+
+/// Fire confetti with the given options, reporting why it could not start.
+///
+/// # Errors
 ///
-/// Panics if the animation state cannot be borrowed (already borrowed elsewhere).
-pub fn confetti(opts: &ConfettiOptions) {
-    if opts.disable_for_reduced_motion && animation::prefers_reduced_motion() {
-        return;
+/// Returns a [`GlitterbombError`] if there is no window, document or body,
+/// or the overlay canvas cannot be created or animated.
+pub fn try_confetti(opts: &ConfettiOptions) -> Result<BurstHandle, GlitterbombError> {
+    #[cfg(feature = "worker")]
+    if let Some(handle) = crate::worker::fire(std::slice::from_ref(opts)) {
+        return handle;
     }
+    DEFAULT.with(|confetti| confetti.try_fire(opts))
+}
 
-    let needs_new_animation = ANIMATION_STATE.with(|state| {
-        let Ok(mut state) = state.try_borrow_mut() else {
-            return false;
-        };
-
-        if state.is_none() {
-            let (canvas, ctx) = animation::create_canvas(opts.z_index);
-            *state = Some(AnimationState::new(canvas, ctx));
-        }
-
-        let s = state.as_mut().expect("state was just set");
-        animation::resize_canvas(&s.canvas);
+/// Fire confetti on a specific canvas element.
+///
+/// Nothing is drawn, apart from a console warning, if the canvas has no 2D
+/// context; use [`try_confetti_on_canvas`] to handle that case yourself.
+pub fn confetti_on_canvas(canvas: &HtmlCanvasElement, opts: &ConfettiOptions) -> BurstHandle {
+    try_confetti_on_canvas(canvas, opts).unwrap_or_else(warned)
+}
 
-        let width = f64::from(s.canvas.width());
-        let height = f64::from(s.canvas.height());
-        let start_x = width * opts.origin.x;
-        let start_y = height * opts.origin.y;
+/// Fire confetti on a specific canvas element, reporting why it could not
+/// start.
+///
+/// Bursts on the same canvas share one animation loop, so rapid calls add
+/// particles instead of erasing each other.
+///
+/// # Errors
+///
+/// Returns [`GlitterbombError::ContextUnavailable`] if the canvas has no 2D
+/// context, or another [`GlitterbombError`] if it cannot be animated.
+pub fn try_confetti_on_canvas(
+    canvas: &HtmlCanvasElement,
+    opts: &ConfettiOptions,
+) -> Result<BurstHandle, GlitterbombError> {
+    canvas_instance(canvas).try_fire(opts)
+}
 
-        for i in 0..opts.particle_count {
-            let color = opts.colors[i as usize % opts.colors.len()];
-            let shape = opts.shapes[animation::random_int(0, opts.shapes.len())];
-            s.particles
-                .push(Particle::new(opts, start_x, start_y, color, shape));
+/// The instance animating `canvas`, creating one if it has none. Instances
+/// whose loops have finished are dropped along the way.
+fn canvas_instance(canvas: &HtmlCanvasElement) -> Confetti {
+    CANVASES.with(|canvases| {
+        let mut canvases = canvases.borrow_mut();
+        canvases.retain(|(c, confetti)| c == canvas || confetti.is_animating());
+        if let Some((_, confetti)) = canvases.iter().find(|(c, _)| c == canvas) {
+            return confetti.clone();
         }
+        let confetti = Confetti::create(Some(canvas), ConfettiSettings::default());
+        canvases.push((canvas.clone(), confetti.clone()));
+        confetti
+    })
+}
 
-        if s.is_animating {
-            false
-        } else {
-            s.is_animating = true;
-            true
-        }
-    });
+/// Reset/stop all confetti animations, on the overlay and on every canvas
+/// passed to [`confetti_on_canvas`], and remove the overlay canvas.
+pub fn reset() {
+    #[cfg(feature = "worker")]
+    crate::worker::reset();
+    DEFAULT.with(Confetti::reset);
+    let canvases = CANVASES.with(|canvases| std::mem::take(&mut *canvases.borrow_mut()));
+    for (_, confetti) in canvases {
+        confetti.reset();
+    }
+}
 
-    if needs_new_animation {
-        start_animation();
+/// Stop the confetti on one canvas passed to [`confetti_on_canvas`], leaving
+/// the overlay and other canvases running.
+pub fn reset_canvas(canvas: &HtmlCanvasElement) {
+    let confetti = CANVASES.with(|canvases| {
+        let mut canvases = canvases.borrow_mut();
+        let index = canvases.iter().position(|(c, _)| c == canvas)?;
+        Some(canvases.swap_remove(index).1)
+    });
+    if let Some(confetti) = confetti {
+        confetti.reset();
     }
 }
 
-/// Fire confetti on a specific canvas element.
+/// Fire every burst of a preset, with one handle for all of them.
+pub fn fire(preset: &Preset) -> BurstHandle {
+    try_fire(preset).unwrap_or_else(warned)
+}
+
+/// Fire every burst of a preset, reporting why it could not start.
 ///
-/// # Panics
+/// # Errors
 ///
-/// Panics if the canvas 2D context cannot be obtained.
-pub fn confetti_on_canvas(canvas: &HtmlCanvasElement, opts: &ConfettiOptions) {
-    if opts.disable_for_reduced_motion && animation::prefers_reduced_motion() {
-        return;
+/// Returns a [`GlitterbombError`] if the overlay cannot be created or
+/// animated; no burst is fired in that case.
+pub fn try_fire(preset: &Preset) -> Result<BurstHandle, GlitterbombError> {
+    #[cfg(feature = "worker")]
+    if let Some(handle) = crate::worker::fire(preset.bursts()) {
+        return handle;
     }
-
-    let ctx = animation::get_context(canvas);
-    let width = f64::from(canvas.width());
-    let height = f64::from(canvas.height());
-    let start_x = width * opts.origin.x;
-    let start_y = height * opts.origin.y;
-
-    let particles: Vec<Particle> = (0..opts.particle_count)
-        .map(|i| {
-            let color = opts.colors[i as usize % opts.colors.len()];
-            let shape = opts.shapes[animation::random_int(0, opts.shapes.len())];
-            Particle::new(opts, start_x, start_y, color, shape)
-        })
-        .collect();
-
-    run_standalone_animation(canvas.clone(), ctx, particles);
+    DEFAULT.with(|confetti| confetti.try_fire_preset(preset))
 }
 
-/// Reset/stop all confetti animations and remove the canvas.
-pub fn reset() {
-    ANIMATION_STATE.with(|state| {
-        if let Ok(mut state) = state.try_borrow_mut() {
-            if let Some(s) = state.take() {
-                s.canvas.remove();
-            }
-        }
-    });
+/// Fire the preset registered or built in under `name`.
+///
+/// Returns `false` if no such preset exists.
+#[must_use]
+pub fn fire_preset(name: &str) -> bool {
+    presets::preset(name).map(|p| fire(&p)).is_some()
 }
 
 /// Fire confetti from both sides of the screen.
 pub fn celebration() {
-    confetti(&ConfettiOptions {
-        particle_count: 50,
-        angle: 60.0,
-        spread: 55.0,
-        origin: Origin { x: 0.0, y: 0.6 },
-        ..Default::default()
-    });
-    confetti(&ConfettiOptions {
-        particle_count: 50,
-        angle: 120.0,
-        spread: 55.0,
-        origin: Origin { x: 1.0, y: 0.6 },
-        ..Default::default()
-    });
+    fire(&Preset::celebration());
 }
 
 /// Fire confetti straight up like fireworks.
 pub fn fireworks() {
-    confetti(&ConfettiOptions {
-        particle_count: 100,
-        spread: 360.0,
-        start_velocity: 30.0,
-        gravity: 0.5,
-        origin: Origin { x: 0.5, y: 0.5 },
-        ..Default::default()
-    });
+    fire(&Preset::fireworks());
 }
 
 /// Gentle snow-like falling confetti.
 pub fn snow() {
-    confetti(&ConfettiOptions {
-        particle_count: 50,
-        spread: 180.0,
-        start_velocity: 10.0,
-        gravity: 0.3,
-        ticks: 400,
-        origin: Origin { x: 0.5, y: 0.0 },
-        colors: vec![Color::WHITE, Color::from_hex("#e0e0e0")],
-        ..Default::default()
-    });
+    fire(&Preset::snow());
 }
 
 /// Confetti cannon from the bottom of the screen.
 pub fn cannon() {
-    confetti(&ConfettiOptions {
-        particle_count: 150,
-        spread: 60.0,
-        start_velocity: 55.0,
-        origin: Origin { x: 0.5, y: 1.0 },
-        ..Default::default()
-    });
+    fire(&Preset::cannon());
 }
 
+/// Fire the default burst. The handle is thenable, so JavaScript can
+/// `await confetti()` and still call `cancel()` on it.
 #[wasm_bindgen(js_name = confetti)]
-pub fn confetti_js() {
-    confetti(&ConfettiOptions::default());
+#[must_use]
+pub fn confetti_js() -> BurstHandle {
+    confetti(&ConfettiOptions::default())
+}
+
+#[wasm_bindgen]
+impl BurstHandle {
+    /// Promise resolving once the bursts are over.
+    #[wasm_bindgen(js_name = promise)]
+    pub fn promise_js(&self) -> Promise {
+        let handle = self.clone();
+        wasm_bindgen_futures::future_to_promise(async move {
+            handle.await;
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    /// Makes the handle thenable: calls `on_finished` once the bursts are
+    /// over, so `await` works on it directly.
+    #[wasm_bindgen(js_name = then)]
+    pub fn then_js(&self, on_finished: Function) -> Promise {
+        let handle = self.clone();
+        wasm_bindgen_futures::future_to_promise(async move {
+            handle.await;
+            on_finished.call0(&JsValue::UNDEFINED)
+        })
+    }
+
+    #[wasm_bindgen(js_name = cancel)]
+    pub fn cancel_js(&self) {
+        self.cancel();
+    }
 }
 
 #[wasm_bindgen(js_name = celebration)]
@@ -168,7 +205,44 @@ pub fn cannon_js() {
     cannon();
 }
 
+#[wasm_bindgen(js_name = firePreset)]
+#[must_use]
+pub fn fire_preset_js(name: &str) -> bool {
+    fire_preset(name)
+}
+
+/// Register a preset from JSON: either one options object or an array of
+/// them for a multi-burst recipe.
+///
+/// # Errors
+///
+/// Returns the parse error message if the JSON is not valid options.
+#[cfg(feature = "json")]
+#[wasm_bindgen(js_name = registerPreset)]
+pub fn register_preset_js(name: &str, json: &str) -> Result<(), JsValue> {
+    let preset = serde_json::from_str::<Preset>(json)
+        .or_else(|_| serde_json::from_str::<ConfettiOptions>(json).map(Preset::from))
+        .map_err(|err| JsValue::from_str(&err.to_string()))?;
+    for opts in preset.bursts() {
+        opts.validate()
+            .map_err(|err| JsValue::from_str(&err.to_string()))?;
+    }
+    presets::register_preset(name, preset);
+    Ok(())
+}
+
 #[wasm_bindgen(js_name = reset)]
 pub fn reset_js() {
     reset();
 }
+
+/// Move the overlay into a module worker at `script_url`.
+///
+/// # Errors
+///
+/// Returns the error message if the worker cannot be started.
+#[cfg(feature = "worker")]
+#[wasm_bindgen(js_name = useWorker)]
+pub fn use_worker_js(script_url: &str) -> Result<(), JsValue> {
+    crate::worker::use_worker(script_url).map_err(|err| JsValue::from_str(&err.to_string()))
+}
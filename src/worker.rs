@@ -0,0 +1,485 @@
+//! Confetti simulated and drawn in a Web Worker.
+//!
+//! [`use_worker`] hands the overlay canvas to a worker with
+//! `transferControlToOffscreen`. From then on the global functions post
+//! each burst to the worker as JSON, and the worker, running
+//! [`run_worker`], animates it on its own thread while the page stays busy.
+
+use crate::animation;
+use crate::error::GlitterbombError;
+use crate::handle::{BurstHandle, BurstTracker, Completion};
+use crate::sim::Simulation;
+use crate::types::ConfettiOptions;
+use crate::viewport::{self, Viewport};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use web_sys::{
+    CanvasRenderingContext2d, DedicatedWorkerGlobalScope, HtmlCanvasElement, MessageEvent,
+    OffscreenCanvas, Worker, WorkerOptions, WorkerType,
+};
+
+/// What the page and the worker tell each other. Apart from the canvas
+/// itself, every message is one of these as a JSON string.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum Message {
+    /// Worker to page: listening, queued messages can be sent.
+    Ready,
+    Spawn {
+        id: u64,
+        bursts: Vec<ConfettiOptions>,
+    },
+    Cancel {
+        id: u64,
+    },
+    /// Worker to page: every particle of the bursts is gone.
+    Finished {
+        id: u64,
+    },
+    Reset,
+    Resize {
+        width: f64,
+        height: f64,
+        scale: f64,
+    },
+}
+
+impl Message {
+    fn encode(&self) -> Option<JsValue> {
+        serde_json::to_string(self)
+            .ok()
+            .map(|json| JsValue::from_str(&json))
+    }
+
+    fn decode(data: &JsValue) -> Option<Self> {
+        serde_json::from_str(&data.as_string()?).ok()
+    }
+}
+
+thread_local! {
+    /// The page's side of the worker, once [`use_worker`] succeeded.
+    static HOST: RefCell<Option<Host>> = const { RefCell::new(None) };
+
+    /// The worker's side, once [`run_worker`] started.
+    static SCENE: Rc<RefCell<Scene>> = Rc::new(RefCell::new(Scene::new()));
+}
+
+struct Host {
+    worker: Worker,
+    /// Messages, with their transfer lists, held until the worker is ready.
+    queue: Option<Vec<(JsValue, Option<js_sys::Array>)>>,
+    pending: HashMap<u64, Completion>,
+    next_id: u64,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_resize: Closure<dyn FnMut()>,
+}
+
+impl Host {
+    fn send(&mut self, message: JsValue, transfer: Option<js_sys::Array>) {
+        if let Some(queue) = &mut self.queue {
+            queue.push((message, transfer));
+            return;
+        }
+        let _ = match transfer {
+            Some(transfer) => self.worker.post_message_with_transfer(&message, &transfer),
+            None => self.worker.post_message(&message),
+        };
+    }
+}
+
+/// Move the overlay canvas and its animation into a Web Worker started from
+/// `script_url`, a module script that loads this package and calls
+/// [`run_worker`]. Later calls to [`confetti`](crate::confetti),
+/// [`fire`](crate::fire) and [`reset`](crate::reset) are forwarded to it.
+///
+/// The overlay stays on the page, click-through, for the rest of its life.
+/// Calling this again does nothing.
+///
+/// # Errors
+///
+/// Returns a [`GlitterbombError`] if the overlay cannot be created, or
+/// [`GlitterbombError::WorkerUnavailable`] if the browser cannot transfer it
+/// or start the worker.
+pub fn use_worker(script_url: &str) -> Result<(), GlitterbombError> {
+    if HOST.with(|host| host.borrow().is_some()) {
+        return Ok(());
+    }
+    let canvas = animation::create_overlay(ConfettiOptions::default().z_index)?;
+    let started = start(script_url, &canvas);
+    if started.is_err() {
+        canvas.remove();
+    }
+    started
+}
+
+fn start(script_url: &str, canvas: &HtmlCanvasElement) -> Result<(), GlitterbombError> {
+    let offscreen = canvas
+        .transfer_control_to_offscreen()
+        .map_err(|_| GlitterbombError::WorkerUnavailable)?;
+    let options = WorkerOptions::new();
+    options.set_type(WorkerType::Module);
+    let worker = Worker::new_with_options(script_url, &options)
+        .map_err(|_| GlitterbombError::WorkerUnavailable)?;
+
+    let on_message = Closure::<dyn FnMut(MessageEvent)>::new(|event: MessageEvent| {
+        match Message::decode(&event.data()) {
+            Some(Message::Ready) => HOST.with(|host| {
+                let mut host = host.borrow_mut();
+                let Some(host) = host.as_mut() else {
+                    return;
+                };
+                for (message, transfer) in host.queue.take().unwrap_or_default() {
+                    host.send(message, transfer);
+                }
+            }),
+            Some(Message::Finished { id }) => {
+                let completion = HOST.with(|host| host.borrow_mut().as_mut()?.pending.remove(&id));
+                drop(completion);
+            }
+            _ => {}
+        }
+    });
+    worker.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+    let on_resize = Closure::<dyn FnMut()>::new(|| post(&resize()));
+    animation::window()?
+        .add_event_listener_with_callback("resize", on_resize.as_ref().unchecked_ref())
+        .map_err(|_| GlitterbombError::WorkerUnavailable)?;
+
+    let mut host = Host {
+        worker,
+        queue: Some(Vec::new()),
+        pending: HashMap::new(),
+        next_id: 0,
+        _on_message: on_message,
+        _on_resize: on_resize,
+    };
+    host.send(
+        offscreen.clone().into(),
+        Some(js_sys::Array::of1(&offscreen)),
+    );
+    if let Some(message) = resize().encode() {
+        host.send(message, None);
+    }
+    HOST.with(|slot| *slot.borrow_mut() = Some(host));
+    Ok(())
+}
+
+fn resize() -> Message {
+    let Viewport {
+        width,
+        height,
+        scale,
+    } = viewport::window();
+    Message::Resize {
+        width,
+        height,
+        scale,
+    }
+}
+
+fn post(message: &Message) {
+    let Some(message) = message.encode() else {
+        return;
+    };
+    HOST.with(|host| {
+        if let Some(host) = host.borrow_mut().as_mut() {
+            host.send(message, None);
+        }
+    });
+}
+
+/// Send `bursts` to the worker, or return `None` if [`use_worker`] has not
+/// been called.
+pub(crate) fn fire(bursts: &[ConfettiOptions]) -> Option<Result<BurstHandle, GlitterbombError>> {
+    HOST.with(|host| host.borrow().is_some())
+        .then(|| send_bursts(bursts))
+}
+
+fn send_bursts(bursts: &[ConfettiOptions]) -> Result<BurstHandle, GlitterbombError> {
+    let bursts: Vec<ConfettiOptions> = bursts
+        .iter()
+        .filter(|o| !(o.disable_for_reduced_motion && animation::prefers_reduced_motion()))
+        .cloned()
+        .collect();
+    if bursts.is_empty() {
+        return Ok(BurstHandle::finished());
+    }
+    let id = HOST.with(|host| {
+        let mut host = host.borrow_mut();
+        let host = host.as_mut().expect("worker is running");
+        host.next_id += 1;
+        host.next_id
+    });
+    let message = Message::Spawn { id, bursts }
+        .encode()
+        .ok_or(GlitterbombError::NotSendable)?;
+
+    let (handle, completion) = BurstHandle::new();
+    HOST.with(|host| {
+        let mut host = host.borrow_mut();
+        let host = host.as_mut().expect("worker is running");
+        host.pending.insert(id, completion);
+        host.send(message, None);
+    });
+    let cancelled = handle.clone();
+    wasm_bindgen_futures::spawn_local(async move {
+        cancelled.await;
+        // Finished bursts were already removed from `pending`.
+        let completion = HOST.with(|host| host.borrow_mut().as_mut()?.pending.remove(&id));
+        if completion.is_some_and(|c| c.is_cancelled()) {
+            post(&Message::Cancel { id });
+        }
+    });
+    Ok(handle)
+}
+
+/// Clear the worker's confetti and resolve every handle, or return `false`
+/// if [`use_worker`] has not been called.
+pub(crate) fn reset() -> bool {
+    let pending = HOST.with(|host| {
+        let mut host = host.borrow_mut();
+        let host = host.as_mut()?;
+        Some(std::mem::take(&mut host.pending))
+    });
+    let Some(pending) = pending else {
+        return false;
+    };
+    drop(pending);
+    post(&Message::Reset);
+    true
+}
+
+/// Entry point of the worker script: listen for the page's canvas and
+/// bursts.
+///
+/// ```js
+/// // confetti-worker.js, started with `useWorker("./confetti-worker.js")`
+/// import init, { runWorker } from "./glitterbomb.js";
+/// await init();
+/// runWorker();
+/// ```
+#[wasm_bindgen(js_name = runWorker)]
+pub fn run_worker() {
+    let scope: DedicatedWorkerGlobalScope = js_sys::global().unchecked_into();
+    let on_message = Closure::<dyn FnMut(MessageEvent)>::new(|event: MessageEvent| {
+        SCENE.with(|scene| Scene::receive(scene, &event.data()));
+    });
+    scope.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    on_message.forget();
+    if let Some(ready) = Message::Ready.encode() {
+        let _ = scope.post_message(&ready);
+    }
+}
+
+/// The worker's particles and the canvas they are drawn on.
+struct Scene {
+    /// The transferred `OffscreenCanvas` and its context, typed as their DOM
+    /// counterparts so the canvas renderer can draw on them unchanged.
+    surface: Option<(HtmlCanvasElement, CanvasRenderingContext2d)>,
+    viewport: Viewport,
+    sim: Simulation,
+    tracker: BurstTracker,
+    bursts: Vec<(u64, BurstHandle)>,
+    is_animating: bool,
+}
+
+impl Scene {
+    fn new() -> Self {
+        Self {
+            surface: None,
+            viewport: Viewport {
+                width: 0.0,
+                height: 0.0,
+                scale: 1.0,
+            },
+            sim: Simulation::new(animation::random),
+            tracker: BurstTracker::new(),
+            bursts: Vec::new(),
+            is_animating: false,
+        }
+    }
+
+    fn receive(scene: &Rc<RefCell<Self>>, data: &JsValue) {
+        let start = {
+            let mut this = scene.borrow_mut();
+            if let Some(canvas) = data.dyn_ref::<OffscreenCanvas>() {
+                this.attach(canvas);
+                return;
+            }
+            match Message::decode(data) {
+                Some(Message::Spawn { id, bursts }) => {
+                    let (width, height) = (this.viewport.width, this.viewport.height);
+                    let ids = bursts
+                        .iter()
+                        .map(|opts| this.sim.spawn(opts, width, height))
+                        .collect();
+                    let handle = this.tracker.handle(ids);
+                    this.bursts.push((id, handle));
+                    !std::mem::replace(&mut this.is_animating, true)
+                }
+                Some(Message::Cancel { id }) => {
+                    if let Some((_, handle)) = this.bursts.iter().find(|(b, _)| *b == id) {
+                        handle.cancel();
+                    }
+                    false
+                }
+                Some(Message::Reset) => {
+                    this.sim.clear();
+                    this.tracker = BurstTracker::new();
+                    this.bursts.clear();
+                    this.clear();
+                    false
+                }
+                Some(Message::Resize {
+                    width,
+                    height,
+                    scale,
+                }) => {
+                    this.viewport = Viewport {
+                        width,
+                        height,
+                        scale,
+                    };
+                    if let Some((canvas, _)) = &this.surface {
+                        this.viewport.apply(canvas);
+                    }
+                    false
+                }
+                Some(Message::Ready | Message::Finished { .. }) | None => false,
+            }
+        };
+        if start {
+            if let Err(err) = Self::animate(Rc::clone(scene)) {
+                animation::warn(&err);
+                scene.borrow_mut().is_animating = false;
+            }
+        }
+    }
+
+    fn attach(&mut self, canvas: &OffscreenCanvas) {
+        let Some(ctx) = canvas.get_context("2d").ok().flatten() else {
+            animation::warn(&GlitterbombError::ContextUnavailable);
+            return;
+        };
+        let canvas: HtmlCanvasElement = canvas.clone().unchecked_into();
+        self.viewport.apply(&canvas);
+        self.surface = Some((canvas, ctx.unchecked_into()));
+    }
+
+    /// Drive the scene every animation frame until its particles are gone.
+    fn animate(scene: Rc<RefCell<Self>>) -> Result<(), GlitterbombError> {
+        let f = Rc::new(RefCell::new(None::<Closure<dyn FnMut(f64)>>));
+        let g = f.clone();
+
+        *g.borrow_mut() = Some(Closure::new(move |timestamp: f64| {
+            let should_continue = scene.borrow_mut().frame(timestamp);
+            let next = should_continue
+                .then(|| request_animation_frame(f.borrow().as_ref().expect("closure exists")));
+            match next {
+                Some(Ok(())) => {}
+                Some(Err(err)) => {
+                    animation::warn(&err);
+                    scene.borrow_mut().is_animating = false;
+                }
+                None => scene.borrow_mut().is_animating = false,
+            }
+        }));
+
+        let first = request_animation_frame(g.borrow().as_ref().expect("closure exists"));
+        first
+    }
+
+    /// Draw one frame. Returns `false` once there is nothing left to draw.
+    fn frame(&mut self, timestamp: f64) -> bool {
+        self.clear();
+        self.sim.advance_to(timestamp / 1000.0);
+        self.tracker.sync(&mut self.sim);
+        self.bursts.retain(|(id, handle)| {
+            let finished = handle.is_finished();
+            if finished {
+                if let Some(message) = (Message::Finished { id: *id }).encode() {
+                    let scope: DedicatedWorkerGlobalScope = js_sys::global().unchecked_into();
+                    let _ = scope.post_message(&message);
+                }
+            }
+            !finished
+        });
+        if let Some((_, ctx)) = &self.surface {
+            self.viewport.enter(ctx);
+            crate::canvas::render_frame(&self.sim, ctx);
+            Viewport::leave(ctx);
+        }
+        !self.sim.is_empty()
+    }
+
+    fn clear(&self) {
+        if let Some((canvas, ctx)) = &self.surface {
+            let width = f64::from(canvas.width());
+            let height = f64::from(canvas.height());
+            ctx.clear_rect(0.0, 0.0, width, height);
+        }
+    }
+}
+
+fn request_animation_frame(f: &Closure<dyn FnMut(f64)>) -> Result<(), GlitterbombError> {
+    let scope: DedicatedWorkerGlobalScope = js_sys::global().unchecked_into();
+    scope
+        .request_animation_frame(f.as_ref().unchecked_ref())
+        .map(|_| ())
+        .map_err(|_| GlitterbombError::AnimationFrameUnavailable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Shape;
+
+    fn round_trip(message: &Message) -> Message {
+        let json = serde_json::to_string(message).expect("encodes");
+        serde_json::from_str(&json).expect("decodes")
+    }
+
+    #[test]
+    fn bursts_survive_the_trip_to_the_worker() {
+        let opts = ConfettiOptions {
+            particle_count: 7,
+            seed: Some(3),
+            shapes: vec![Shape::Heart, Shape::Ribbon],
+            ..Default::default()
+        };
+        let Message::Spawn { id, bursts } = round_trip(&Message::Spawn {
+            id: 4,
+            bursts: vec![opts],
+        }) else {
+            panic!("not a spawn message");
+        };
+        assert_eq!(id, 4);
+        assert_eq!(bursts[0].particle_count, 7);
+        assert_eq!(bursts[0].seed, Some(3));
+        assert_eq!(bursts[0].shapes, [Shape::Heart, Shape::Ribbon]);
+    }
+
+    #[test]
+    fn messages_are_tagged_by_type() {
+        let json = serde_json::to_string(&Message::Finished { id: 2 }).expect("encodes");
+        assert_eq!(json, r#"{"type":"finished","id":2}"#);
+        assert!(matches!(round_trip(&Message::Reset), Message::Reset));
+    }
+
+    #[test]
+    fn image_shapes_cannot_be_sent() {
+        let image = crate::types::ImageShape::from_rgba(1, 1, vec![0; 4]).expect("image");
+        let message = Message::Spawn {
+            id: 1,
+            bursts: vec![ConfettiOptions {
+                shapes: vec![Shape::Image(image)],
+                ..Default::default()
+            }],
+        };
+        assert!(serde_json::to_string(&message).is_err());
+    }
+}